@@ -0,0 +1,18 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Build script
+
+use std::env::var_os;
+
+/// Link the defmt linker script when logging through defmt
+fn main() {
+    if var_os("CARGO_FEATURE_DEFMT").is_some() {
+        println!("cargo:rustc-link-arg=-Tdefmt.x");
+    }
+}
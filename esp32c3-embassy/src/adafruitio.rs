@@ -40,21 +40,41 @@ impl AdafruitIoClient for HttpClient {}
 
 /// An error within a request to Adafruit IO
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error {
     /// Error from HTTP client
-    Http(#[expect(unused, reason = "Never read directly")] HttpError),
+    Http(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        HttpError,
+    ),
 
     /// A time component is out of range
-    TimeComponentRange(#[expect(unused, reason = "Never read directly")] TimeComponentRangeError),
+    TimeComponentRange(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+        TimeComponentRangeError,
+    ),
 
     /// An integer valued returned by the server could not be parsed
-    ParseInt(#[expect(unused, reason = "Never read directly")] ParseIntError),
+    ParseInt(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+        ParseIntError,
+    ),
 
     /// Text returned by the server is not valid UTF-8
-    Utf8(#[expect(unused, reason = "Never read directly")] Utf8Error),
+    Utf8(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+        Utf8Error,
+    ),
 
     /// Text returned by the server is not valid JSON
-    Json(#[expect(unused, reason = "Never read directly")] JSONParsingError),
+    Json(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+        JSONParsingError,
+    ),
 }
 
 impl From<HttpError> for Error {
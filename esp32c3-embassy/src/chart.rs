@@ -0,0 +1,481 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Chart of a measurement over the history
+//!
+//! Values are handled in hundredths of their unit, so that axes can be computed
+//! with integer arithmetic.
+
+use core::convert::Infallible;
+use core::fmt::Write as _;
+
+use embedded_graphics::mono_font::iso_8859_1::FONT_6X10 as LABEL_FONT;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Line;
+use embedded_graphics::primitives::PrimitiveStyle;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::Alignment;
+use embedded_graphics::text::Baseline;
+use embedded_graphics::text::Text;
+use embedded_graphics::text::TextStyle;
+use embedded_graphics::text::TextStyleBuilder;
+
+use heapless::HistoryBuf;
+use heapless::String;
+
+use time::Duration;
+use time::OffsetDateTime;
+
+use waveshare_154bv2_rs::Color as TriColor;
+
+use crate::dashboard::Error;
+use crate::domain::Quantity;
+use crate::domain::Reading;
+use crate::locale::Locale;
+use crate::units::Units;
+use crate::Sample;
+
+/// Style for axis labels
+const LABEL_STYLE: MonoTextStyle<TriColor> = MonoTextStyle::new(&LABEL_FONT, TriColor::Black);
+
+/// Style for axes and ticks
+const AXIS_STYLE: PrimitiveStyle<TriColor> = PrimitiveStyle::with_stroke(TriColor::Black, 1);
+
+/// Style for the plotted line
+const LINE_STYLE: PrimitiveStyle<TriColor> = PrimitiveStyle::with_stroke(TriColor::Chromatic, 1);
+
+/// Width reserved for y-axis labels, in pixels
+const Y_LABELS_WIDTH: i32 = 40;
+
+/// Height reserved for x-axis labels, in pixels
+const X_LABELS_HEIGHT: i32 = 13;
+
+/// Margin above the plot, leaving room for the topmost y-axis label
+const TOP_MARGIN: i32 = 5;
+
+/// Length of tick marks, in pixels
+const TICK_LENGTH: i32 = 2;
+
+/// Maximal number of intervals between y-axis ticks
+const MAX_Y_INTERVALS: i32 = 3;
+
+/// Maximal number of x-axis ticks
+const MAX_X_TICKS: i64 = 3;
+
+/// Candidate steps between y-axis ticks, in hundredths of unit
+const Y_STEPS: [i32; 12] = [
+    10, 20, 50, 100, 200, 500, 1000, 2000, 5000, 10000, 20000, 50000,
+];
+
+/// Candidate intervals between x-axis ticks, in seconds
+const X_INTERVALS: [i64; 9] = [
+    5 * 60,
+    10 * 60,
+    15 * 60,
+    30 * 60,
+    60 * 60,
+    2 * 60 * 60,
+    3 * 60 * 60,
+    6 * 60 * 60,
+    12 * 60 * 60,
+];
+
+/// Number of seconds in a day
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// A chart of a quantity over the history
+///
+/// Time is on the x axis, with ticks at round local times, and the y axis
+/// is scaled to the range of values.
+pub struct Chart<'history, const SIZE: usize> {
+    /// History of readings
+    history: &'history HistoryBuf<Reading, SIZE>,
+
+    /// Plotted quantity
+    quantity: Quantity,
+
+    /// Units for plotted values
+    units: Units,
+
+    /// Locale for time labels
+    locale: Locale,
+
+    /// Area of the chart, including labels
+    area: Rectangle,
+}
+
+impl<'history, const SIZE: usize> Chart<'history, SIZE> {
+    /// Create a chart
+    pub fn new(
+        history: &'history HistoryBuf<Reading, SIZE>,
+        quantity: Quantity,
+        units: Units,
+        locale: Locale,
+        area: Rectangle,
+    ) -> Self {
+        Self {
+            history,
+            quantity,
+            units,
+            locale,
+            area,
+        }
+    }
+
+    /// Draw the chart
+    ///
+    /// Nothing is drawn if the history is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a label cannot be formatted
+    pub fn draw<DISPLAY>(&self, display: &mut DISPLAY) -> Result<(), Error>
+    where
+        DISPLAY: DrawTarget<Color = TriColor, Error = Infallible>,
+    {
+        let Some(values) = self.value_range() else {
+            return Ok(());
+        };
+        let plot = self.plot_area();
+        let scale = Scale::new(values, plot);
+
+        Line::new(plot.top_left(), plot.bottom_left())
+            .into_styled(AXIS_STYLE)
+            .draw(display)?;
+        Line::new(plot.bottom_left(), plot.bottom_right())
+            .into_styled(AXIS_STYLE)
+            .draw(display)?;
+
+        draw_y_ticks(display, &scale)?;
+        self.draw_x_ticks(display, &scale)?;
+        self.draw_line(display, &scale)?;
+
+        Ok(())
+    }
+
+    /// Draw ticks and labels on the x axis
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a label cannot be formatted
+    fn draw_x_ticks<DISPLAY>(&self, display: &mut DISPLAY, scale: &Scale) -> Result<(), Error>
+    where
+        DISPLAY: DrawTarget<Color = TriColor, Error = Infallible>,
+    {
+        let Some(interval) = scale.time_interval() else {
+            return Ok(());
+        };
+        let Some(&(last_time, _)) = self.history.recent() else {
+            return Ok(());
+        };
+        let offset = i64::from(last_time.offset().whole_seconds());
+
+        let style = label_style(Alignment::Center, Baseline::Top);
+        let bottom = scale.plot.bottom;
+
+        // Ticks are placed at multiples of the interval in local time
+        let first = -(-(scale.start + offset)).div_euclid(interval) * interval - offset;
+        let ticks = (0..)
+            .map(|index| first + index * interval)
+            .take_while(|&time| time <= scale.end);
+        for time in ticks {
+            let x = scale.x(time);
+            Line::new(Point::new(x, bottom), Point::new(x, bottom + TICK_LENGTH))
+                .into_styled(AXIS_STYLE)
+                .draw(display)?;
+
+            // Times are shifted from the last reading, so that they keep its
+            // offset
+            let local_time = last_time + Duration::seconds(time - last_time.unix_timestamp());
+            let label = format_time_of_day(&local_time, self.locale)?;
+            let position = Point::new(x, bottom + TICK_LENGTH + 1);
+            let text = Text::with_text_style(&label, position, LABEL_STYLE, style);
+
+            // Labels at the edges are shifted to stay within the chart
+            let bounds = text.bounding_box();
+            let left_overflow = self.area.top_left.x - bounds.top_left.x;
+            let right_overflow = bounds
+                .bottom_right()
+                .map_or(0_i32, |corner| corner.x - scale.plot.right);
+            let shift = left_overflow.max(0_i32) - right_overflow.max(0_i32);
+            text.translate(Point::new(shift, 0)).draw(display)?;
+        }
+
+        Ok(())
+    }
+
+    /// Draw the line connecting all values
+    fn draw_line<DISPLAY>(&self, display: &mut DISPLAY, scale: &Scale) -> Result<(), Infallible>
+    where
+        DISPLAY: DrawTarget<Color = TriColor, Error = Infallible>,
+    {
+        let mut previous = None;
+        for &(time, ref sample) in self.history.oldest_ordered() {
+            let point = Point::new(
+                scale.x(time.unix_timestamp()),
+                scale.y(self.hundredths(sample)),
+            );
+            let start = previous.unwrap_or(point);
+            Line::new(start, point)
+                .into_styled(LINE_STYLE)
+                .draw(display)?;
+            previous = Some(point);
+        }
+
+        Ok(())
+    }
+
+    /// Compute the range of times and values in the history
+    ///
+    /// Return `None` if the history is empty.
+    fn value_range(&self) -> Option<ValueRange> {
+        let mut readings = self.history.oldest_ordered();
+        let &(first_time, ref first_sample) = readings.next()?;
+        let first_value = self.hundredths(first_sample);
+
+        let initial = ValueRange {
+            start: first_time.unix_timestamp(),
+            end: first_time.unix_timestamp(),
+            min: first_value,
+            max: first_value,
+        };
+        let range = readings.fold(initial, |range, &(time, ref sample)| {
+            let value = self.hundredths(sample);
+            ValueRange {
+                start: range.start.min(time.unix_timestamp()),
+                end: range.end.max(time.unix_timestamp()),
+                min: range.min.min(value),
+                max: range.max.max(value),
+            }
+        });
+
+        Some(range)
+    }
+
+    /// Get the plotted value of a sample in hundredths of unit
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "Measurements are far from the limits of i32"
+    )]
+    fn hundredths(&self, sample: &Sample) -> i32 {
+        (self.units.value(self.quantity, sample) * 100.0) as i32
+    }
+
+    /// Compute the area of the plot, excluding labels
+    #[expect(
+        clippy::cast_possible_wrap,
+        reason = "Display dimensions are far from the limits of i32"
+    )]
+    fn plot_area(&self) -> PlotArea {
+        let Rectangle { top_left, size } = self.area;
+        PlotArea {
+            left: top_left.x + Y_LABELS_WIDTH,
+            right: top_left.x + size.width as i32 - 1,
+            top: top_left.y + TOP_MARGIN,
+            bottom: top_left.y + size.height as i32 - X_LABELS_HEIGHT,
+        }
+    }
+}
+
+/// Range of times and values in the history
+#[derive(Clone, Copy, Debug)]
+struct ValueRange {
+    /// First time as Unix timestamp
+    start: i64,
+
+    /// Last time as Unix timestamp
+    end: i64,
+
+    /// Minimal value in hundredths of unit
+    min: i32,
+
+    /// Maximal value in hundredths of unit
+    max: i32,
+}
+
+/// Coordinates of the plot edges, all inclusive
+#[derive(Clone, Copy, Debug)]
+struct PlotArea {
+    /// Leftmost column, where the y axis is drawn
+    left: i32,
+
+    /// Rightmost column
+    right: i32,
+
+    /// Topmost row
+    top: i32,
+
+    /// Bottommost row, where the x axis is drawn
+    bottom: i32,
+}
+
+impl PlotArea {
+    /// Get the top-left corner
+    const fn top_left(self) -> Point {
+        Point::new(self.left, self.top)
+    }
+
+    /// Get the bottom-left corner
+    const fn bottom_left(self) -> Point {
+        Point::new(self.left, self.bottom)
+    }
+
+    /// Get the bottom-right corner
+    const fn bottom_right(self) -> Point {
+        Point::new(self.right, self.bottom)
+    }
+}
+
+/// Mapping of times and values to display coordinates
+#[derive(Clone, Copy, Debug)]
+struct Scale {
+    /// Plot area
+    plot: PlotArea,
+
+    /// First time as Unix timestamp
+    start: i64,
+
+    /// Last time as Unix timestamp
+    end: i64,
+
+    /// Value at the bottom of the plot, in hundredths of unit
+    low: i32,
+
+    /// Value at the top of the plot, in hundredths of unit
+    high: i32,
+
+    /// Step between y-axis ticks, in hundredths of unit
+    step: i32,
+}
+
+impl Scale {
+    /// Create a scale fitting a range of values in a plot area
+    ///
+    /// The y axis is extended to multiples of the smallest step yielding
+    /// few enough ticks.
+    fn new(range: ValueRange, plot: PlotArea) -> Self {
+        let bounds = |step: i32| {
+            let low = range.min.div_euclid(step) * step;
+            let high = (-(-range.max).div_euclid(step) * step).max(low + step);
+            (low, high)
+        };
+        let step = Y_STEPS
+            .into_iter()
+            .find(|&step| {
+                let (low, high) = bounds(step);
+                (high - low) / step <= MAX_Y_INTERVALS
+            })
+            .unwrap_or(Y_STEPS[Y_STEPS.len() - 1]);
+        let (low, high) = bounds(step);
+
+        Self {
+            plot,
+            start: range.start,
+            end: range.end,
+            low,
+            high,
+            step,
+        }
+    }
+
+    /// Map a time to a column
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "Result is between the plot edges"
+    )]
+    fn x(&self, time: i64) -> i32 {
+        let span = self.end - self.start;
+        if span == 0 {
+            return self.plot.left;
+        }
+        let width = i64::from(self.plot.right - self.plot.left);
+        self.plot.left + ((time - self.start) * width / span) as i32
+    }
+
+    /// Map a value to a row
+    fn y(&self, value: i32) -> i32 {
+        let height = self.plot.bottom - self.plot.top;
+        self.plot.bottom - (value - self.low) * height / (self.high - self.low)
+    }
+
+    /// Select the smallest interval between x-axis ticks yielding few enough
+    /// ticks
+    ///
+    /// Return `None` if all readings have the same time.
+    fn time_interval(&self) -> Option<i64> {
+        let span = self.end - self.start;
+        if span == 0 {
+            return None;
+        }
+        let interval = X_INTERVALS
+            .into_iter()
+            .find(|&interval| span / interval < MAX_X_TICKS)
+            .unwrap_or(SECONDS_PER_DAY);
+        Some(interval)
+    }
+}
+
+/// Draw ticks and labels on the y axis
+///
+/// # Errors
+///
+/// Returns an error if a label cannot be formatted
+fn draw_y_ticks<DISPLAY>(display: &mut DISPLAY, scale: &Scale) -> Result<(), Error>
+where
+    DISPLAY: DrawTarget<Color = TriColor, Error = Infallible>,
+{
+    let style = label_style(Alignment::Right, Baseline::Middle);
+    let left = scale.plot.left;
+
+    let values = (0_i32..)
+        .map(|index| scale.low + index * scale.step)
+        .take_while(|&value| value <= scale.high);
+    for value in values {
+        let y = scale.y(value);
+        Line::new(Point::new(left - TICK_LENGTH, y), Point::new(left, y))
+            .into_styled(AXIS_STYLE)
+            .draw(display)?;
+
+        let label = format_value(value, scale.step)?;
+        let position = Point::new(left - TICK_LENGTH - 1, y);
+        Text::with_text_style(&label, position, LABEL_STYLE, style).draw(display)?;
+    }
+
+    Ok(())
+}
+
+/// Create a text style for labels
+fn label_style(alignment: Alignment, baseline: Baseline) -> TextStyle {
+    TextStyleBuilder::new()
+        .alignment(alignment)
+        .baseline(baseline)
+        .build()
+}
+
+/// Format a value in hundredths of unit as an axis label
+///
+/// The decimal digit is omitted when the step is a whole unit.
+fn format_value(value: i32, step: i32) -> Result<String<8>, Error> {
+    let mut string: String<8> = String::new();
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "Measurements are far from the precision limits of f32"
+    )]
+    let value = value as f32 / 100.0;
+    let precision = usize::from(step % 100_i32 != 0_i32);
+    write!(&mut string, "{value:.precision$}")?;
+    Ok(string)
+}
+
+/// Format a time of day as an axis label
+fn format_time_of_day(time: &OffsetDateTime, locale: Locale) -> Result<String<8>, Error> {
+    let mut string: String<8> = String::new();
+    locale.write_time(&mut string, time)?;
+    Ok(string)
+}
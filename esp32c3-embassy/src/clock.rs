@@ -139,15 +139,23 @@ fn duration_to_next_rounded_wakeup(now: Duration, period: Duration) -> Duration
 
 /// A clock error
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error {
     /// A time component is out of range
-    TimeComponentRange(#[expect(unused, reason = "Never read directly")] TimeComponentRange),
+    TimeComponentRange(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+        TimeComponentRange,
+    ),
 
     /// The time is invalid in the current time offset
     InvalidInOffset,
 
     /// Error synchronizing time from World Time API
-    Synchronization(#[expect(unused, reason = "Never read directly")] AdafruitIoError),
+    Synchronization(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        AdafruitIoError,
+    ),
 }
 
 impl From<TimeComponentRange> for Error {
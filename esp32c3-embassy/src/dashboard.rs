@@ -13,9 +13,14 @@
 use core::fmt::Write as _;
 
 use embedded_graphics::mono_font::iso_8859_1::FONT_10X20 as FONT;
+use embedded_graphics::mono_font::iso_8859_1::FONT_6X10 as SMALL_FONT;
 use embedded_graphics::mono_font::MonoTextStyle;
 use embedded_graphics::mono_font::MonoTextStyleBuilder;
 use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::PrimitiveStyle;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::primitives::Styled;
+use embedded_graphics::primitives::Triangle;
 use embedded_graphics::text::Text;
 
 use embedded_layout::align::Align;
@@ -28,17 +33,27 @@
 use uom::si::f32::Pressure;
 use uom::si::f32::Ratio as Humidity;
 use uom::si::f32::ThermodynamicTemperature as Temperature;
-use uom::si::pressure::hectopascal;
 use uom::si::ratio::percent;
-use uom::si::thermodynamic_temperature::degree_celsius;
 
+use heapless::HistoryBuf;
 use heapless::String;
 
 use time::OffsetDateTime;
 
 use waveshare_154bv2_rs::Color as TriColor;
 
-use crate::Sample;
+use crate::chart::Chart;
+use crate::domain::Quantity;
+use crate::domain::Reading;
+use crate::locale::HourFormat;
+use crate::locale::Language;
+use crate::locale::Locale;
+use crate::statistics::Statistics;
+use crate::statistics::StatisticsConfig;
+use crate::statistics::Trend;
+use crate::units::PressureUnit;
+use crate::units::TemperatureUnit;
+use crate::units::Units;
 
 /// Style for black text
 pub const BLACK_STYLE: MonoTextStyle<TriColor> = MonoTextStyleBuilder::new()
@@ -54,63 +69,278 @@
     .background_color(TriColor::White)
     .build();
 
+/// Style for minimal and maximal values
+pub const SMALL_STYLE: MonoTextStyle<TriColor> = MonoTextStyleBuilder::new()
+    .font(&SMALL_FONT)
+    .text_color(TriColor::Black)
+    .background_color(TriColor::White)
+    .build();
+
+/// Width of the display, in pixels
+const DISPLAY_WIDTH: u32 = 200;
+
+/// Widest temperature value and unit, as in `-10.0°C` or `100.4°F`
+const WIDEST_TEMPERATURE: &str = "-10.0°C";
+
+/// Widest humidity value and unit
+const WIDEST_HUMIDITY: &str = "  100 %";
+
+/// Widest pressure value and unit, as in `1013.2 hPa` or `29.92 inHg`
+const WIDEST_PRESSURE: &str = "1013.2 hPa";
+
+// Every row must fit in the width of the display, in all languages and hour
+// formats
+const _: () = {
+    let mut index = 0;
+    while index < Language::ALL.len() {
+        let labels = Locale::new(Language::ALL[index]).labels();
+        assert!(
+            fits(&[labels.temperature, WIDEST_TEMPERATURE]),
+            "Temperature row is too wide"
+        );
+        assert!(
+            fits(&[labels.humidity, WIDEST_HUMIDITY]),
+            "Humidity row is too wide"
+        );
+        assert!(
+            fits(&[labels.pressure, WIDEST_PRESSURE]),
+            "Pressure row is too wide"
+        );
+
+        let twelve_hour =
+            Locale::new(Language::ALL[index]).with_hour_format(HourFormat::TwelveHour);
+        let twenty_four_hour =
+            Locale::new(Language::ALL[index]).with_hour_format(HourFormat::TwentyFourHour);
+        assert!(
+            fits(&[labels.updated_at, twelve_hour.longest_time()]),
+            "Update time row is too wide with 12 hours"
+        );
+        assert!(
+            fits(&[labels.updated_at, twenty_four_hour.longest_time()]),
+            "Update time row is too wide with 24 hours"
+        );
+
+        index += 1;
+    }
+};
+
+/// Style for trend arrows
+const ARROW_STYLE: PrimitiveStyle<TriColor> = PrimitiveStyle::with_fill(TriColor::Black);
+
+/// Size of trend arrows, in pixels
+const ARROW_SIZE: i32 = 8;
+
+/// Quantity plotted on the history chart
+const CHART_QUANTITY: Quantity = Quantity::Temperature;
+
+/// Vertical space between the measurement rows and the history chart
+const CHART_MARGIN: u32 = 4;
+
 /// Draw a dashboard
-pub fn draw<DISPLAY>(
+///
+/// The most recent reading is shown as text, together with minimal and
+/// maximal values and trends, and the whole history is plotted on a chart
+/// below it.
+/// Nothing is drawn if the history is empty.
+pub fn draw<DISPLAY, const SIZE: usize>(
     display: &mut DISPLAY,
     now: &OffsetDateTime,
-    sample: &Sample,
+    history: &HistoryBuf<Reading, SIZE>,
+    units: Units,
+    locale: Locale,
+    statistics: StatisticsConfig,
 ) -> Result<(), Error>
 where
     DISPLAY: DrawTarget<Color = TriColor, Error = Infallible>,
 {
+    #[expect(
+        clippy::pattern_type_mismatch,
+        reason = "Allow to avoid complicate match expression"
+    )]
+    let Some((_, sample)) = history.recent() else {
+        return Ok(());
+    };
+    let (Some(temperature_statistics), Some(humidity_statistics), Some(pressure_statistics)) = (
+        Statistics::from_history(history, Quantity::Temperature, statistics),
+        Statistics::from_history(history, Quantity::Humidity, statistics),
+        Statistics::from_history(history, Quantity::Pressure, statistics),
+    ) else {
+        return Ok(());
+    };
+
     let display_area = display.bounding_box();
-    let temperature = format_temperature(sample.temperature)?;
+    let temperature = format_temperature(sample.temperature, units.temperature)?;
     let humidity = format_humidity(sample.humidity)?;
-    let pressure = format_pressure(sample.pressure)?;
-    let time = format_time(now)?;
+    let pressure = format_pressure(sample.pressure, units.pressure)?;
+    let temperature_extremes = format_extremes(
+        &temperature_statistics,
+        Quantity::Temperature,
+        units,
+        locale,
+    )?;
+    let humidity_extremes =
+        format_extremes(&humidity_statistics, Quantity::Humidity, units, locale)?;
+    let pressure_extremes =
+        format_extremes(&pressure_statistics, Quantity::Pressure, units, locale)?;
+    let date = format_date(now, locale)?;
+    let time = format_time(now, locale)?;
+    let labels = locale.labels();
 
-    let temperature_layout = lay_out_measurement("Temperature: ", &temperature, " C");
-    let humidity_layout = lay_out_measurement("Humidity: ", &humidity, " %");
-    let pressure_layout = lay_out_measurement("Pressure: ", &pressure, " hPa");
-    let time_layout = lay_out_update_time(&time);
+    let temperature_layout = lay_out_measurement(
+        labels.temperature,
+        &temperature,
+        unit_separator(Quantity::Temperature),
+        units.symbol(Quantity::Temperature),
+        &temperature_extremes,
+        temperature_statistics.trend,
+    );
+    let humidity_layout = lay_out_measurement(
+        labels.humidity,
+        &humidity,
+        unit_separator(Quantity::Humidity),
+        units.symbol(Quantity::Humidity),
+        &humidity_extremes,
+        humidity_statistics.trend,
+    );
+    let pressure_layout = lay_out_measurement(
+        labels.pressure,
+        &pressure,
+        unit_separator(Quantity::Pressure),
+        units.symbol(Quantity::Pressure),
+        &pressure_extremes,
+        pressure_statistics.trend,
+    );
+    let date_layout = Text::new(&date, Point::zero(), BLACK_STYLE);
+    let time_layout = lay_out_update_time(labels.updated_at, &time);
 
-    LinearLayout::vertical(
+    let rows = LinearLayout::vertical(
         Chain::new(temperature_layout)
             .append(humidity_layout)
             .append(pressure_layout)
+            .append(date_layout)
             .append(time_layout),
     )
     .with_alignment(horizontal::Left)
     .arrange()
-    .align_to(&display_area, horizontal::Left, vertical::Top)
-    .draw(display)?;
+    .align_to(&display_area, horizontal::Left, vertical::Top);
+    rows.draw(display)?;
+
+    let chart_offset = rows.bounds().size.height + CHART_MARGIN;
+    let chart_area = Rectangle::new(
+        display_area.top_left + Size::new(0, chart_offset),
+        display_area.size.saturating_sub(Size::new(0, chart_offset)),
+    );
+    Chart::new(history, CHART_QUANTITY, units, locale, chart_area).draw(display)?;
 
     Ok(())
 }
 
 /// Lay out a measurement row
+///
+/// The current value is shown with its trend, and minimal and maximal
+/// values below it.
 fn lay_out_measurement<'text>(
     label: &'text str,
     value: &'text str,
+    separator: &'text str,
     unit: &'text str,
+    extremes: &'text str,
+    trend: Trend,
 ) -> impl Drawable<Color = TriColor> + View + 'text {
-    LinearLayout::horizontal(
+    let current = LinearLayout::horizontal(
         Chain::new(Text::new(label, Point::zero(), BLACK_STYLE))
             .append(Text::new(value, Point::zero(), CHROMATIC_STYLE))
+            .append(Text::new(separator, Point::zero(), BLACK_STYLE))
             .append(Text::new(unit, Point::zero(), BLACK_STYLE)),
     )
     .with_alignment(vertical::Center)
-    .arrange()
+    .arrange();
+
+    // The trend is shown next to the extremes, leaving room for longer
+    // labels in other languages
+    let summary = LinearLayout::horizontal(
+        Chain::new(trend_arrow(trend))
+            .append(Text::new(" ", Point::zero(), SMALL_STYLE))
+            .append(Text::new(extremes, Point::zero(), SMALL_STYLE)),
+    )
+    .with_alignment(vertical::Center)
+    .arrange();
+
+    LinearLayout::vertical(Chain::new(current).append(summary))
+        .with_alignment(horizontal::Left)
+        .arrange()
+}
+
+/// Get the separator between the value of a quantity and its unit
+///
+/// Degree symbols are attached to the value, leaving room for negative and
+/// three-digit temperatures.
+const fn unit_separator(quantity: Quantity) -> &'static str {
+    match quantity {
+        Quantity::Temperature => "",
+        Quantity::Humidity | Quantity::Pressure => " ",
+    }
+}
+
+/// Create an arrow pointing in the direction of a trend
+///
+/// Steady trends are shown as an arrow pointing right.
+fn trend_arrow(trend: Trend) -> Styled<Triangle, PrimitiveStyle<TriColor>> {
+    let (first, second, third) = match trend {
+        Trend::Rising => (
+            (0_i32, ARROW_SIZE),
+            (ARROW_SIZE, ARROW_SIZE),
+            (ARROW_SIZE / 2_i32, 0_i32),
+        ),
+        Trend::Steady => (
+            (0_i32, 0_i32),
+            (0_i32, ARROW_SIZE),
+            (ARROW_SIZE, ARROW_SIZE / 2_i32),
+        ),
+        Trend::Falling => (
+            (0_i32, 0_i32),
+            (ARROW_SIZE, 0_i32),
+            (ARROW_SIZE / 2_i32, ARROW_SIZE),
+        ),
+    };
+    Triangle::new(first.into(), second.into(), third.into()).into_styled(ARROW_STYLE)
+}
+
+/// Check whether texts drawn in a row fit in the width of the display
+const fn fits(texts: &[&str]) -> bool {
+    let character_width = FONT.character_size.width + FONT.character_spacing;
+    let mut width = 0;
+    let mut index = 0;
+    while index < texts.len() {
+        width += count_characters(texts[index]) * character_width;
+        index += 1;
+    }
+    width <= DISPLAY_WIDTH
+}
+
+/// Count the characters in a string
+///
+/// Continuation bytes of multi-byte characters are not counted.
+const fn count_characters(text: &str) -> u32 {
+    let bytes = text.as_bytes();
+    let mut count = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] & 0b1100_0000 != 0b1000_0000 {
+            count += 1;
+        }
+        index += 1;
+    }
+    count
 }
 
 /// Lay out the update time row
-#[allow(
-    clippy::needless_lifetimes,
-    reason = "Lifetime annotation is actually needed"
-)]
-fn lay_out_update_time(now: &str) -> impl Drawable<Color = TriColor> + View + '_ {
+fn lay_out_update_time<'text>(
+    label: &'text str,
+    now: &'text str,
+) -> impl Drawable<Color = TriColor> + View + 'text {
     LinearLayout::horizontal(
-        Chain::new(Text::new("Updated at ", Point::zero(), BLACK_STYLE)).append(Text::new(
+        Chain::new(Text::new(label, Point::zero(), BLACK_STYLE)).append(Text::new(
             now,
             Point::zero(),
             CHROMATIC_STYLE,
@@ -120,18 +350,49 @@ fn lay_out_update_time(now: &str) -> impl Drawable<Color = TriColor> + View + '_
     .arrange()
 }
 
-/// Format a time as `HOUR:MINUTE`
-fn format_time(now: &OffsetDateTime) -> Result<String<5>, Error> {
-    let mut string: String<5> = String::new();
-    write!(&mut string, "{:0>2}:{:0>2}", now.hour(), now.minute())?;
+/// Format a time of day
+fn format_time(now: &OffsetDateTime, locale: Locale) -> Result<String<8>, Error> {
+    let mut string: String<8> = String::new();
+    locale.write_time(&mut string, now)?;
+
+    Ok(string)
+}
+
+/// Format a date
+fn format_date(now: &OffsetDateTime, locale: Locale) -> Result<String<24>, Error> {
+    let mut string: String<24> = String::new();
+    locale.write_date(&mut string, now)?;
+
+    Ok(string)
+}
 
+/// Format minimal and maximal values of a quantity
+fn format_extremes(
+    statistics: &Statistics,
+    quantity: Quantity,
+    units: Units,
+    locale: Locale,
+) -> Result<String<24>, FmtError> {
+    let mut string: String<24> = String::new();
+    let labels = locale.labels();
+    let minimum = units.convert(quantity, statistics.minimum);
+    let maximum = units.convert(quantity, statistics.maximum);
+    let precision = units.precision(quantity);
+    write!(
+        &mut string,
+        "{} {minimum:.precision$}  {} {maximum:.precision$}",
+        labels.minimum, labels.maximum,
+    )?;
     Ok(string)
 }
 
 /// Format a temperature value
-fn format_temperature(temperature: Temperature) -> Result<String<10>, FmtError> {
+fn format_temperature(
+    temperature: Temperature,
+    unit: TemperatureUnit,
+) -> Result<String<10>, FmtError> {
     let mut string: String<10> = String::new();
-    write!(&mut string, "{:>3.1}", temperature.get::<degree_celsius>())?;
+    write!(&mut string, "{:>3.1}", unit.value(temperature))?;
     Ok(string)
 }
 
@@ -143,20 +404,22 @@ fn format_humidity(humidity: Humidity) -> Result<String<10>, FmtError> {
 }
 
 /// Format a pressure value
-fn format_pressure(pressure: Pressure) -> Result<String<10>, FmtError> {
+fn format_pressure(pressure: Pressure, unit: PressureUnit) -> Result<String<10>, FmtError> {
     let mut string: String<10> = String::new();
-    write!(&mut string, "{:>5.1}", pressure.get::<hectopascal>())?;
+    let precision = unit.precision();
+    write!(&mut string, "{:>5.precision$}", unit.value(pressure))?;
     Ok(string)
 }
 
 /// An error
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error {
     /// An impossible error existing only to satisfy the type system
     Impossible(Infallible),
 
     /// An error occurred while formatting a string
-    Fmt(FmtError),
+    Fmt(#[cfg_attr(feature = "defmt", defmt(Debug2Format))] FmtError),
 }
 
 impl From<FmtError> for Error {
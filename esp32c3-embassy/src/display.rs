@@ -8,8 +8,7 @@
 
 //! Task for reporting sensor value on a WaveShare E-INK display
 
-use log::error;
-use log::info;
+use core::time::Duration;
 
 use embassy_time::Delay;
 
@@ -24,6 +23,8 @@
 
 use embedded_hal::digital::OutputPin;
 
+use embedded_graphics::draw_target::DrawTarget;
+
 use time::OffsetDateTime;
 
 use esp_hal::gpio::Input;
@@ -33,36 +34,93 @@
 
 use heapless::HistoryBuf;
 
-use uom::si::pressure::hectopascal;
-use uom::si::ratio::percent;
 use uom::si::thermodynamic_temperature::degree_celsius;
 
+use waveshare_154bv2_rs::AsyncBufferedDisplay;
 use waveshare_154bv2_rs::AsyncDisplay;
-use waveshare_154bv2_rs::Buffer;
+use waveshare_154bv2_rs::Color;
+use waveshare_154bv2_rs::CompressedImage;
+use waveshare_154bv2_rs::Epd1in54Buffer;
+use waveshare_154bv2_rs::Epd1in54bV2;
 use waveshare_154bv2_rs::Error as DisplayError;
+use waveshare_154bv2_rs::Frame;
+use waveshare_154bv2_rs::RefreshMode;
+use waveshare_154bv2_rs::WriteStrategy;
 
 use crate::dashboard::draw as draw_dashboard;
 use crate::dashboard::Error as DashboardError;
+use crate::domain::Quantity;
 use crate::domain::Reading;
 use crate::domain::Sample;
+use crate::locale::Locale;
+use crate::logging::error;
+use crate::logging::info;
+use crate::logging::Float2Format;
+use crate::statistics::StatisticsConfig;
+use crate::units::Units;
 
-/// Task for displaying samples
-#[embassy_executor::task]
-pub async fn update_task(
+/// Maximal time to wait for the display to complete an operation
+///
+/// A full tri-color refresh takes around 15 seconds.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maximal number of bytes written to the display in a single SPI transfer
+///
+/// This is the size of 40 rows of the display, and SPI DMA buffers are sized
+/// accordingly.
+pub const WRITE_CHUNK_SIZE: usize = 40 * 25;
+
+/// The display used for the dashboard
+pub type Display = AsyncDisplay<
+    ExclusiveDevice<SpiDmaBus<'static, Async>, Output<'static>, Delay>,
+    Input<'static>,
+    Output<'static>,
+    Output<'static>,
+    Delay,
+    Epd1in54bV2,
+>;
+
+/// Create the display
+pub fn create(
     spi_device: ExclusiveDevice<SpiDmaBus<'static, Async>, Output<'static>, Delay>,
     busy: Input<'static>,
     rst: Output<'static>,
     dc: Output<'static>,
+) -> Display {
+    AsyncDisplay::new(spi_device, busy, rst, dc, Delay)
+        .with_write_strategy(WriteStrategy::Chunked(WRITE_CHUNK_SIZE))
+        .with_busy_timeout(BUSY_TIMEOUT)
+        .with_recovery_on_busy_timeout(true)
+}
+
+/// Task for displaying samples
+#[embassy_executor::task]
+pub async fn update_task(
+    display: Display,
     receiver: Receiver<'static, NoopRawMutex, Reading, 3>,
     history: &'static mut HistoryBuf<(OffsetDateTime, Sample), 96>,
+    splash: Option<CompressedImage<'static>>,
+    units: Units,
+    locale: Locale,
+    statistics: StatisticsConfig,
 ) {
-    info!("Create display");
-    let mut display = AsyncDisplay::new_with_individual_writes(spi_device, busy, rst, dc, Delay);
+    // The last frame is kept to skip refreshing an unchanged dashboard
+    let mut frame = Frame::new();
+    let mut display =
+        AsyncBufferedDisplay::new(display, Epd1in54Buffer::new()).with_frame(&mut frame);
+
+    // The display is initialized when it is woken up for the first time.
+    // Errors are only logged, so that the sensor task is never blocked on a
+    // full channel.
+    if let Some(splash) = splash {
+        if let Err(error) = show_splash(&splash, display.display_mut()).await {
+            error!("Could not show splash screen: {:?}", error);
+        }
 
-    info!("Initialize display");
-    if let Err(error) = display.initialize().await {
-        error!(" Cannot initialize display: {error:?}");
-        return;
+        info!("Put display to sleep");
+        if let Err(error) = display.display_mut().sleep().await {
+            error!("Could not put display to sleep: {:?}", error);
+        }
     }
 
     loop {
@@ -72,17 +130,34 @@ pub async fn update_task(
 
         history.write(reading);
 
-        if let Err(error) = report(&now, history, &mut display).await {
-            error!("Could not report sample: {error:?}");
+        if let Err(error) = report(&now, history, &mut display, units, locale, statistics).await {
+            error!("Could not report sample: {:?}", error);
+        }
+
+        info!("Put display to sleep");
+        if let Err(error) = display.display_mut().sleep().await {
+            error!("Could not put display to sleep: {:?}", error);
         }
     }
 }
 
 /// Report a new sample
-async fn report<SPI, BUSY, RST, DC, DELAY>(
+async fn report<
+    SPI,
+    BUSY,
+    RST,
+    DC,
+    DELAY,
+    const WIDTH: usize,
+    const HEIGHT: usize,
+    const BYTE_SIZE: usize,
+>(
     now: &OffsetDateTime,
     history: &HistoryBuf<Reading, 96>,
-    display: &mut AsyncDisplay<SPI, BUSY, RST, DC, DELAY>,
+    display: &mut AsyncBufferedDisplay<'_, SPI, BUSY, RST, DC, DELAY, WIDTH, HEIGHT, BYTE_SIZE>,
+    units: Units,
+    locale: Locale,
+    statistics: StatisticsConfig,
 ) -> Result<(), ReportError>
 where
     SPI: SpiDevice,
@@ -96,37 +171,82 @@ async fn report<SPI, BUSY, RST, DC, DELAY>(
         reason = "Allow to avoid complicate match expression"
     )]
     if let Some((_, sample)) = history.recent() {
-        log_sample(sample);
-
-        let mut buffer = Buffer::new();
+        log_sample(sample, units);
 
         info!("Draw dashboard on buffer");
-        draw_dashboard(&mut buffer, now, sample)?;
+        let Ok(()) = display.clear(Color::White);
+        draw_dashboard(display, now, history, units, locale, statistics)?;
+
+        if display.is_up_to_date(RefreshMode::Full) {
+            info!("Dashboard is unchanged, skip refresh");
+            return Ok(());
+        }
+
+        info!("Wake display");
+        display.display_mut().wake().await?;
+
+        info!("Set display temperature for waveform selection");
+        display
+            .display_mut()
+            .set_temperature(sample.temperature.get::<degree_celsius>())
+            .await?;
 
+        // Measurements are drawn in the chromatic color, so a full refresh is
+        // needed to show them
         info!("Draw buffer on display");
-        display.draw_buffer(&buffer).await?;
+        display.flush(RefreshMode::Full).await?;
     }
 
     Ok(())
 }
 
+/// Show a splash screen
+///
+/// The splash screen is drawn directly from flash, without a buffer.
+async fn show_splash<SPI, BUSY, RST, DC, DELAY>(
+    splash: &CompressedImage<'_>,
+    display: &mut AsyncDisplay<SPI, BUSY, RST, DC, DELAY>,
+) -> Result<(), DisplayError>
+where
+    SPI: SpiDevice,
+    BUSY: Wait,
+    RST: OutputPin,
+    DC: OutputPin,
+    DELAY: DelayNs,
+{
+    info!("Wake display");
+    display.wake().await?;
+
+    info!("Draw splash screen on display");
+    display.draw_compressed(splash, RefreshMode::Full).await?;
+
+    Ok(())
+}
+
 /// Print a sample to log
-fn log_sample(sample: &Sample) {
-    let temperature = sample.temperature.get::<degree_celsius>();
-    let humidity = sample.humidity.get::<percent>();
-    let pressure = sample.pressure.get::<hectopascal>();
+fn log_sample(sample: &Sample, units: Units) {
+    let temperature = Float2Format(units.value(Quantity::Temperature, sample));
+    let temperature_unit = units.symbol(Quantity::Temperature);
+    let humidity = Float2Format(units.value(Quantity::Humidity, sample));
+    let humidity_unit = units.symbol(Quantity::Humidity);
+    let pressure = Float2Format(units.value(Quantity::Pressure, sample));
+    let pressure_unit = units.symbol(Quantity::Pressure);
 
     info!("Received sample");
-    info!(" ┣ Temperature: {temperature:.2} C");
-    info!(" ┣ Humidity:    {humidity:.2} %");
-    info!(" ┗ Pressure:    {pressure:.2} hPa");
+    info!(" ┣ Temperature: {} {}", temperature, temperature_unit);
+    info!(" ┣ Humidity:    {} {}", humidity, humidity_unit);
+    info!(" ┗ Pressure:    {} {}", pressure, pressure_unit);
 }
 
 /// An error
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 enum ReportError {
     /// An error occurred while updating the display
-    Display(#[expect(unused, reason = "Never read directly")] DisplayError),
+    Display(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        DisplayError,
+    ),
 
     /// An error occurred while drawing the dashboard
     Dashboard(DashboardError),
@@ -21,6 +21,11 @@
 
 use bme280_rs::Sample as Bme280Sample;
 
+#[cfg(feature = "defmt")]
+use defmt::Format;
+#[cfg(feature = "defmt")]
+use defmt::Formatter;
+
 /// A sample
 #[derive(Clone, Debug, Default)]
 pub struct Sample {
@@ -79,11 +84,49 @@ fn try_from(sample: Bme280Sample) -> Result<Self, Self::Error> {
     }
 }
 
+#[cfg(feature = "defmt")]
+impl Format for Sample {
+    fn format(&self, formatter: Formatter<'_>) {
+        defmt::write!(
+            formatter,
+            "Sample {{ temperature: {=f32} C, humidity: {=f32} %, pressure: {=f32} hPa }}",
+            self.temperature.get::<degree_celsius>(),
+            self.humidity.get::<percent>(),
+            self.pressure.get::<hectopascal>(),
+        );
+    }
+}
+
 /// A reading, i.e. a pair (time, sample)
 pub type Reading = (OffsetDateTime, Sample);
 
+/// A quantity measured in a sample
+#[derive(Clone, Copy, Debug)]
+pub enum Quantity {
+    /// Temperature in degrees Celsius
+    Temperature,
+
+    /// Humidity in percent
+    Humidity,
+
+    /// Pressure in hectopascals
+    Pressure,
+}
+
+impl Quantity {
+    /// Get the value of the quantity in a sample
+    pub fn value(self, sample: &Sample) -> f32 {
+        match self {
+            Self::Temperature => sample.temperature.get::<degree_celsius>(),
+            Self::Humidity => sample.humidity.get::<percent>(),
+            Self::Pressure => sample.pressure.get::<hectopascal>(),
+        }
+    }
+}
+
 /// An error
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error {
     /// A measurement was missing
     MissingMeasurement,
@@ -15,7 +15,6 @@
 use embassy_net::tcp::ConnectError as TcpConnectError;
 use embassy_net::tcp::Error as TcpError;
 use embassy_net::Stack;
-use log::debug;
 
 use reqwless::client::HttpClient;
 use reqwless::client::TlsConfig;
@@ -28,6 +27,8 @@
 
 use rand_core::Rng as _;
 
+use crate::logging::debug;
+use crate::logging::Debug2Format;
 use crate::RngWrapper;
 
 /// Response size
@@ -80,7 +81,7 @@ pub fn new(stack: Stack<'static>, rng: RngWrapper) -> Self {
 
 impl ClientTrait for Client {
     async fn send_request(&mut self, url: &str) -> Result<Vec<u8, RESPONSE_SIZE>, Error> {
-        debug!("Send HTTPs request to {url}");
+        debug!("Send HTTPs request to {}", url);
 
         debug!("Create DNS socket");
         let dns_socket = DnsSocket::new(self.stack);
@@ -106,7 +107,7 @@ async fn send_request(&mut self, url: &str) -> Result<Vec<u8, RESPONSE_SIZE>, Er
         debug!("Send HTTP request");
         let response = request.send(&mut buffer).await?;
 
-        debug!("Response status: {:?}", response.status);
+        debug!("Response status: {:?}", Debug2Format(&response.status));
 
         let buffer = response.body().read_to_end().await?;
 
@@ -120,21 +121,31 @@ async fn send_request(&mut self, url: &str) -> Result<Vec<u8, RESPONSE_SIZE>, Er
 
 /// An error within an HTTP request
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error {
     /// Response was too large
-    ResponseTooLarge(CapacityError),
+    ResponseTooLarge(#[cfg_attr(feature = "defmt", defmt(Debug2Format))] CapacityError),
 
     /// Error within TCP streams
     Tcp(TcpError),
 
     /// Error within TCP connection
-    TcpConnect(#[expect(unused, reason = "Never read directly")] TcpConnectError),
+    TcpConnect(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        TcpConnectError,
+    ),
 
     /// Error within DNS system
-    Dns(#[expect(unused, reason = "Never read directly")] DnsError),
+    Dns(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        DnsError,
+    ),
 
     /// Error in HTTP client
-    Reqless(#[expect(unused, reason = "Never read directly")] ReqlessError),
+    Reqless(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        ReqlessError,
+    ),
 }
 
 impl From<CapacityError> for Error {
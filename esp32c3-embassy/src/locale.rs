@@ -0,0 +1,235 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Localization of the dashboard
+//!
+//! All strings are in ISO-8859-1, so that they can be drawn with the
+//! dashboard fonts.
+
+use core::fmt::Result as FmtResult;
+use core::fmt::Write;
+
+use time::OffsetDateTime;
+
+/// A language
+#[derive(Clone, Copy, Debug)]
+pub enum Language {
+    /// English
+    English,
+
+    /// Italian
+    Italian,
+
+    /// German
+    German,
+
+    /// French
+    French,
+}
+
+impl Language {
+    /// All languages
+    pub const ALL: [Self; 4] = [Self::English, Self::Italian, Self::German, Self::French];
+
+    /// Get the labels in this language
+    const fn labels(self) -> &'static Labels {
+        match self {
+            Self::English => &ENGLISH,
+            Self::Italian => &ITALIAN,
+            Self::German => &GERMAN,
+            Self::French => &FRENCH,
+        }
+    }
+}
+
+/// A format for hours
+#[derive(Clone, Copy, Debug)]
+pub enum HourFormat {
+    /// Hours from 1 to 12, followed by `AM` or `PM`
+    TwelveHour,
+
+    /// Hours from 0 to 23
+    TwentyFourHour,
+}
+
+/// Labels and names in a language
+#[derive(Debug)]
+pub struct Labels {
+    /// Label of the temperature row
+    pub temperature: &'static str,
+
+    /// Label of the humidity row
+    pub humidity: &'static str,
+
+    /// Label of the pressure row
+    pub pressure: &'static str,
+
+    /// Label of the update time row
+    pub updated_at: &'static str,
+
+    /// Label of minimal values
+    pub minimum: &'static str,
+
+    /// Label of maximal values
+    pub maximum: &'static str,
+
+    /// Abbreviated names of weekdays, starting from Monday
+    weekdays: [&'static str; 7],
+
+    /// Abbreviated names of months, starting from January
+    months: [&'static str; 12],
+}
+
+/// Labels in English
+const ENGLISH: Labels = Labels {
+    temperature: "Temperature: ",
+    humidity: "Humidity: ",
+    pressure: "Pressure: ",
+    updated_at: "Updated at ",
+    minimum: "min",
+    maximum: "max",
+    weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    months: [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ],
+};
+
+/// Labels in Italian
+const ITALIAN: Labels = Labels {
+    temperature: "Temperatura: ",
+    humidity: "Umidità: ",
+    pressure: "Press.: ",
+    updated_at: "Aggiornato ",
+    minimum: "min",
+    maximum: "max",
+    weekdays: ["Lun", "Mar", "Mer", "Gio", "Ven", "Sab", "Dom"],
+    months: [
+        "gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic",
+    ],
+};
+
+/// Labels in German
+const GERMAN: Labels = Labels {
+    temperature: "Temperatur: ",
+    humidity: "Luftfeuchte: ",
+    pressure: "Druck: ",
+    updated_at: "Stand: ",
+    minimum: "min",
+    maximum: "max",
+    weekdays: ["Mo.", "Di.", "Mi.", "Do.", "Fr.", "Sa.", "So."],
+    months: [
+        "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sep.", "Okt.", "Nov.",
+        "Dez.",
+    ],
+};
+
+/// Labels in French
+const FRENCH: Labels = Labels {
+    temperature: "Température: ",
+    humidity: "Humidité: ",
+    pressure: "Pression: ",
+    updated_at: "Mis à jour ",
+    minimum: "min",
+    maximum: "max",
+    weekdays: ["Lun.", "Mar.", "Mer.", "Jeu.", "Ven.", "Sam.", "Dim."],
+    months: [
+        "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.",
+        "déc.",
+    ],
+};
+
+/// A locale for the dashboard
+///
+/// Times use 24 hours unless set otherwise with
+/// [`Locale::with_hour_format()`].
+#[derive(Clone, Copy, Debug)]
+pub struct Locale {
+    /// Language of labels and names
+    language: Language,
+
+    /// Format of hours
+    hour_format: HourFormat,
+}
+
+impl Locale {
+    /// Create a locale for a language, with 24-hour times
+    pub const fn new(language: Language) -> Self {
+        Self {
+            language,
+            hour_format: HourFormat::TwentyFourHour,
+        }
+    }
+
+    /// Set the format of hours
+    #[must_use]
+    pub const fn with_hour_format(mut self, hour_format: HourFormat) -> Self {
+        self.hour_format = hour_format;
+        self
+    }
+
+    /// Get the labels
+    pub const fn labels(self) -> &'static Labels {
+        self.language.labels()
+    }
+
+    /// Write the time of day
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the writer fails
+    pub fn write_time<WRITER: Write>(
+        self,
+        writer: &mut WRITER,
+        time: &OffsetDateTime,
+    ) -> FmtResult {
+        let minute = time.minute();
+        match self.hour_format {
+            HourFormat::TwentyFourHour => write!(writer, "{:0>2}:{minute:0>2}", time.hour()),
+            HourFormat::TwelveHour => {
+                let suffix = if time.hour() < 12 { "AM" } else { "PM" };
+                let hour = match time.hour() % 12 {
+                    0 => 12,
+                    hour => hour,
+                };
+                write!(writer, "{hour}:{minute:0>2} {suffix}")
+            }
+        }
+    }
+
+    /// Get the longest time of day written by [`Locale::write_time()`]
+    pub const fn longest_time(self) -> &'static str {
+        match self.hour_format {
+            HourFormat::TwelveHour => "12:59 PM",
+            HourFormat::TwentyFourHour => "23:59",
+        }
+    }
+
+    /// Write the date, including the day of the week
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the writer fails
+    pub fn write_date<WRITER: Write>(
+        self,
+        writer: &mut WRITER,
+        time: &OffsetDateTime,
+    ) -> FmtResult {
+        let labels = self.labels();
+        let weekday = labels.weekdays[usize::from(time.weekday().number_days_from_monday())];
+        let month = labels.months[usize::from(u8::from(time.month()) - 1)];
+        let day = time.day();
+        let year = time.year();
+        match self.language {
+            Language::English => write!(writer, "{weekday}, {month} {day}, {year}"),
+            Language::Italian | Language::French => {
+                write!(writer, "{weekday} {day} {month} {year}")
+            }
+            Language::German => write!(writer, "{weekday}, {day}. {month} {year}"),
+        }
+    }
+}
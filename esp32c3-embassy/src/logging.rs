@@ -7,21 +7,118 @@
 // https://opensource.org/licenses/Apache-2.0
 
 //! Functions for setting up the logging system
+//!
+//! Messages are logged through `log` and printed as text, or through `defmt`
+//! when the `defmt` feature is enabled.
+//! Logging macros and formatting adapters are provided for either crate, so
+//! that modules log in the same way with both.
+//! Arguments are passed positionally, since `defmt` does not support inlined
+//! format arguments.
 
+#[cfg(not(feature = "defmt"))]
+use core::fmt::Debug;
+#[cfg(not(feature = "defmt"))]
+use core::fmt::Display;
+#[cfg(not(feature = "defmt"))]
+use core::fmt::Formatter;
+#[cfg(not(feature = "defmt"))]
+use core::fmt::Result as FmtResult;
+#[cfg(not(feature = "defmt"))]
 use core::str::FromStr;
 
+#[cfg(not(feature = "defmt"))]
 use log::max_level;
+#[cfg(not(feature = "defmt"))]
 use log::set_logger_racy;
+#[cfg(not(feature = "defmt"))]
 use log::set_max_level_racy;
-use log::trace;
+#[cfg(not(feature = "defmt"))]
 use log::Level;
+#[cfg(not(feature = "defmt"))]
 use log::LevelFilter;
+#[cfg(not(feature = "defmt"))]
 use log::Log;
+#[cfg(not(feature = "defmt"))]
 use log::Metadata;
+#[cfg(not(feature = "defmt"))]
 use log::Record;
 
+// The `log` macros are wrapped so that messages with positional arguments,
+// as required by `defmt`, do not trigger `clippy::uninlined_format_args`
+#[cfg(not(feature = "defmt"))]
+/// Log a message at debug level through `log`
+macro_rules! log_debug {
+    ($($argument:tt)+) => {
+        ::log::debug!($($argument)+)
+    };
+}
+
+#[cfg(not(feature = "defmt"))]
+/// Log a message at error level through `log`
+macro_rules! log_error {
+    ($($argument:tt)+) => {
+        ::log::error!($($argument)+)
+    };
+}
+
+#[cfg(not(feature = "defmt"))]
+/// Log a message at info level through `log`
+macro_rules! log_info {
+    ($($argument:tt)+) => {
+        ::log::info!($($argument)+)
+    };
+}
+
+#[cfg(not(feature = "defmt"))]
+/// Log a message at trace level through `log`
+macro_rules! log_trace {
+    ($($argument:tt)+) => {
+        ::log::trace!($($argument)+)
+    };
+}
+
+#[cfg(not(feature = "defmt"))]
+/// Log a message at warn level through `log`
+macro_rules! log_warn {
+    ($($argument:tt)+) => {
+        ::log::warn!($($argument)+)
+    };
+}
+
+#[cfg(not(feature = "defmt"))]
+pub(crate) use log_debug as debug;
+#[cfg(not(feature = "defmt"))]
+pub(crate) use log_error as error;
+#[cfg(not(feature = "defmt"))]
+pub(crate) use log_info as info;
+#[cfg(not(feature = "defmt"))]
+pub(crate) use log_trace as trace;
+#[cfg(not(feature = "defmt"))]
+pub(crate) use log_warn as warn;
+
+#[cfg(feature = "defmt")]
+pub use defmt::debug;
+#[cfg(feature = "defmt")]
+pub use defmt::error;
+#[cfg(feature = "defmt")]
+pub use defmt::info;
+#[cfg(feature = "defmt")]
+pub use defmt::trace;
+#[cfg(feature = "defmt")]
+pub use defmt::warn;
+#[cfg(feature = "defmt")]
+pub use defmt::Debug2Format;
+#[cfg(feature = "defmt")]
+pub use defmt::Display2Format;
+
+#[cfg(not(feature = "defmt"))]
 use esp_println::println;
 
+// The global defmt logger is defined in esp-println
+#[cfg(feature = "defmt")]
+use esp_println as _;
+
+#[cfg(not(feature = "defmt"))]
 /// Setup logging
 ///
 /// To change the log level change the `env` section in `.cargo/config.toml`
@@ -55,9 +152,24 @@ pub fn setup() {
     trace!("Logger is ready");
 }
 
+#[cfg(feature = "defmt")]
+/// Setup logging
+///
+/// Messages are encoded by defmt and printed by `esp-println`, so they must
+/// be decoded by `espflash monitor --log-format defmt`.
+///
+/// To change the log level change the `env` section in `.cargo/config.toml`
+/// or remove it and set the environment variable `DEFMT_LOG` manually before
+/// running `cargo run`.
+pub fn setup() {
+    trace!("Logger is ready");
+}
+
+#[cfg(not(feature = "defmt"))]
 /// Logger that prints messages to console
 struct EspPrintlnLogger;
 
+#[cfg(not(feature = "defmt"))]
 impl Log for EspPrintlnLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
         if metadata.target().starts_with("esp_wifi") {
@@ -108,3 +220,51 @@ fn log(&self, record: &Record) {
 
     fn flush(&self) {}
 }
+
+#[cfg(not(feature = "defmt"))]
+/// Adapter formatting a value through its `Debug` implementation
+///
+/// This mirrors `defmt::Debug2Format`, and it is used to log values that do
+/// not implement `defmt::Format`.
+pub struct Debug2Format<'value, VALUE: Debug + ?Sized>(pub &'value VALUE);
+
+#[cfg(not(feature = "defmt"))]
+impl<VALUE: Debug + ?Sized> Debug for Debug2Format<'_, VALUE> {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        self.0.fmt(formatter)
+    }
+}
+
+#[cfg(not(feature = "defmt"))]
+/// Adapter formatting a value through its `Display` implementation
+///
+/// This mirrors `defmt::Display2Format`, and it is used to log values that
+/// do not implement `defmt::Format`.
+pub struct Display2Format<'value, VALUE: Display + ?Sized>(pub &'value VALUE);
+
+#[cfg(not(feature = "defmt"))]
+impl<VALUE: Display + ?Sized> Display for Display2Format<'_, VALUE> {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        self.0.fmt(formatter)
+    }
+}
+
+/// Adapter formatting a floating point number with two decimal digits
+///
+/// Messages encoded by defmt are formatted by the host, which does not
+/// support setting the precision, so there the number is printed in full.
+pub struct Float2Format(pub f32);
+
+#[cfg(not(feature = "defmt"))]
+impl Display for Float2Format {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        write!(formatter, "{:.2}", self.0)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Float2Format {
+    fn format(&self, formatter: defmt::Formatter<'_>) {
+        defmt::write!(formatter, "{=f32}", self.0);
+    }
+}
@@ -15,9 +15,6 @@
 
 use core::convert::Infallible;
 
-use log::error;
-use log::info;
-
 use embassy_executor::Spawner;
 
 use embassy_time::Delay;
@@ -74,11 +71,21 @@
 use esp_rtos::main;
 use esp_rtos::start as start_rtos;
 
+use time::Duration as TimeDuration;
 use time::OffsetDateTime;
 
 use heapless::HistoryBuf;
 use heapless::String;
 
+use uom::si::f32::Pressure;
+use uom::si::f32::Ratio as Humidity;
+use uom::si::f32::TemperatureInterval;
+use uom::si::pressure::hectopascal;
+use uom::si::ratio::percent;
+use uom::si::temperature_interval::degree_celsius;
+
+use waveshare_154bv2_rs::CompressedImage;
+
 use embedded_hal_bus::spi::ExclusiveDevice;
 
 use esp_backtrace as _;
@@ -88,15 +95,26 @@
 mod adafruitio;
 
 mod logging;
+use self::logging::error;
+use self::logging::info;
 use self::logging::setup as setup_logging;
+use self::logging::Display2Format;
 
 mod sensor;
 use self::sensor::sample_task as sample_sensor_task;
 
+mod chart;
+
 mod dashboard;
 
+mod locale;
+use self::locale::Language;
+use self::locale::Locale;
+
 mod display;
+use self::display::create as create_display;
 use self::display::update_task as update_display_task;
+use self::display::WRITE_CHUNK_SIZE as DISPLAY_WRITE_CHUNK_SIZE;
 
 mod cell;
 use self::cell::SyncUnsafeCell;
@@ -118,6 +136,15 @@
 mod sleep;
 use self::sleep::enter_deep as enter_deep_sleep;
 
+mod splash;
+use self::splash::BOOT as BOOT_SPLASH;
+
+mod statistics;
+use self::statistics::StatisticsConfig;
+
+mod units;
+use self::units::Units;
+
 mod wifi;
 use self::wifi::connect as connect_to_wifi;
 use self::wifi::Error as WifiError;
@@ -132,6 +159,31 @@
 /// Period to wait before going to deep sleep
 const AWAKE_PERIOD: Duration = Duration::from_secs(300);
 
+/// Units for presenting samples on the dashboard and in log
+///
+/// Use [`Units::IMPERIAL`] or a custom combination created with
+/// [`Units::new()`] to present samples in other units.
+const UNITS: Units = Units::METRIC;
+
+/// Locale for the dashboard
+///
+/// Labels are available in English, Italian, German and French.
+/// Use [`Locale::with_hour_format()`] to show times with 12 hours.
+const LOCALE: Locale = Locale::new(Language::English);
+
+/// Configuration of minimal and maximal values and trends on the dashboard
+///
+/// Trend thresholds are changes over an hour.
+fn statistics_config() -> StatisticsConfig {
+    StatisticsConfig {
+        extremes_window: TimeDuration::hours(6),
+        trend_readings: 6,
+        temperature_trend_threshold: TemperatureInterval::new::<degree_celsius>(0.5),
+        humidity_trend_threshold: Humidity::new::<percent>(2.0),
+        pressure_trend_threshold: Pressure::new::<hectopascal>(0.5),
+    }
+}
+
 /// SSID for WiFi network
 const WIFI_SSID: &str = env!("WIFI_SSID");
 
@@ -144,23 +196,39 @@
 /// A channel between sensor sampler and display updater
 static CHANNEL: StaticCell<Channel<NoopRawMutex, Reading, 3>> = StaticCell::new();
 
+/// Frequency of SPI bus for the display
+const SPI_FREQUENCY_KHZ: u32 = 4_000;
+
+/// Maximal number of bytes covered by a DMA descriptor
+const DESCRIPTOR_CHUNK_SIZE: usize = 4092;
+
 /// Size of SPI DMA descriptors
-const DESCRIPTORS_SIZE: usize = 8 * 3;
+const DESCRIPTORS_SIZE: usize = BUFFERS_SIZE.div_ceil(DESCRIPTOR_CHUNK_SIZE);
 
 /// Descriptors for SPI DMA
 static DESCRIPTORS: StaticCell<[DmaDescriptor; DESCRIPTORS_SIZE]> = StaticCell::new();
 
+/// Size of SPI DMA RX descriptors
+const RX_DESCRIPTORS_SIZE: usize = 1;
+
 /// RX descriptors for SPI DMA
-static RX_DESCRIPTORS: StaticCell<[DmaDescriptor; DESCRIPTORS_SIZE]> = StaticCell::new();
+static RX_DESCRIPTORS: StaticCell<[DmaDescriptor; RX_DESCRIPTORS_SIZE]> = StaticCell::new();
 
 /// Size of SPI DMA buffers
-const BUFFERS_SIZE: usize = 8 * 3;
+///
+/// Whole display rows are written in a single transfer.
+const BUFFERS_SIZE: usize = DISPLAY_WRITE_CHUNK_SIZE;
 
 /// Buffer for SPI DMA
 static BUFFER: StaticCell<[u8; BUFFERS_SIZE]> = StaticCell::new();
 
+/// Size of SPI DMA RX buffers
+///
+/// Nothing is read from the display, so the buffer is kept small.
+const RX_BUFFERS_SIZE: usize = 32;
+
 /// RX Buffer for SPI DMA
-static RX_BUFFER: StaticCell<[u8; BUFFERS_SIZE]> = StaticCell::new();
+static RX_BUFFER: StaticCell<[u8; RX_BUFFERS_SIZE]> = StaticCell::new();
 
 /// Stored boot count between deep sleep cycles
 ///
@@ -188,7 +256,8 @@ async fn main(spawner: Spawner) {
     // SAFETY:
     // This is pointing to a valid value
     let boot_count: &'static mut _ = unsafe { boot_count.unwrap_unchecked() };
-    info!("Current boot count = {boot_count}");
+    info!("Current boot count = {}", *boot_count);
+    let cold_boot = *boot_count == 0;
     *boot_count += 1;
 
     // SAFETY:
@@ -198,8 +267,8 @@ async fn main(spawner: Spawner) {
     // This is pointing to a valid value
     let history: &'static mut _ = unsafe { history.unwrap_unchecked() };
 
-    if let Err(error) = main_fallible(spawner, history).await {
-        error!("Error while running firmware: {error:?}");
+    if let Err(error) = main_fallible(spawner, history, cold_boot).await {
+        error!("Error while running firmware: {:?}", error);
     }
 }
 
@@ -207,6 +276,7 @@ async fn main(spawner: Spawner) {
 async fn main_fallible(
     spawner: Spawner,
     history: &'static mut HistoryBuf<(OffsetDateTime, Sample), 96>,
+    cold_boot: bool,
 ) -> Result<(), Error> {
     let peripherals = initialize_esp_hal(EspConfig::default().with_cpu_clock(CpuClock::max()));
 
@@ -218,17 +288,11 @@ async fn main_fallible(
 
     let rng = Rng::new();
 
-    let clock = load_clock(spawner, peripherals.WIFI, rng).await?;
-
-    info!("Now is {}", clock.now()?);
-
-    info!("Turn off cold LED");
-    let mut cold_led = Output::new(peripherals.GPIO18, Level::High, OutputConfig::default());
-    cold_led.set_low();
-
     info!("History contains {} elements", history.len());
 
+    // The splash screen is refreshed while the clock is synchronized
     info!("Setup display task");
+    let splash = cold_boot.then_some(BOOT_SPLASH);
     let sender = setup_display_task(
         spawner,
         DisplayPeripherals {
@@ -242,8 +306,20 @@ async fn main_fallible(
             dma: peripherals.DMA_CH0,
         },
         history,
+        splash,
+        UNITS,
+        LOCALE,
+        statistics_config(),
     )?;
 
+    let clock = load_clock(spawner, peripherals.WIFI, rng).await?;
+
+    info!("Now is {}", Display2Format(&clock.now()?));
+
+    info!("Turn off cold LED");
+    let mut cold_led = Output::new(peripherals.GPIO18, Level::High, OutputConfig::default());
+    cold_led.set_low();
+
     info!("Setup sensor task");
     setup_sensor_task(
         spawner,
@@ -318,14 +394,20 @@ struct DisplayPeripherals {
 }
 
 /// Setup display task
+///
+/// If a splash screen is passed, it is shown before any sample.
 fn setup_display_task(
     spawner: Spawner,
     peripherals: DisplayPeripherals,
     history: &'static mut HistoryBuf<(OffsetDateTime, Sample), 96>,
+    splash: Option<CompressedImage<'static>>,
+    units: Units,
+    locale: Locale,
+    statistics: StatisticsConfig,
 ) -> Result<Sender<'static, NoopRawMutex, (OffsetDateTime, Sample), 3>, Error> {
     info!("Create SPI bus");
     let spi_config = SpiConfig::default()
-        .with_frequency(Rate::from_khz(25_u32))
+        .with_frequency(Rate::from_khz(SPI_FREQUENCY_KHZ))
         .with_mode(SpiMode::_0);
     let spi_bus = Spi::new(peripherals.spi2, spi_config)?
         .with_sck(peripherals.sclk)
@@ -334,10 +416,10 @@ fn setup_display_task(
     info!("Wrap SPI bus in a SPI DMA");
     let descriptors: &'static mut _ = DESCRIPTORS.init([DmaDescriptor::EMPTY; DESCRIPTORS_SIZE]);
     let rx_descriptors: &'static mut _ =
-        RX_DESCRIPTORS.init([DmaDescriptor::EMPTY; DESCRIPTORS_SIZE]);
+        RX_DESCRIPTORS.init([DmaDescriptor::EMPTY; RX_DESCRIPTORS_SIZE]);
 
     let buffer: &'static mut _ = BUFFER.init([0; BUFFERS_SIZE]);
-    let rx_buffer: &'static mut _ = RX_BUFFER.init([0; BUFFERS_SIZE]);
+    let rx_buffer: &'static mut _ = RX_BUFFER.init([0; RX_BUFFERS_SIZE]);
 
     let spi_dma: SpiDma<'_, Blocking> = spi_bus.with_dma(peripherals.dma);
     let spi_dma: SpiDma<'_, Async> = spi_dma.into_async();
@@ -357,6 +439,9 @@ fn setup_display_task(
     info!("Create SPI device");
     let spi_device = ExclusiveDevice::new(spi_dma_bus, cs, Delay)?;
 
+    info!("Create display");
+    let display = create_display(spi_device, busy, rst, dc);
+
     info!("Create channel");
     let channel: &'static mut _ = CHANNEL.init(Channel::new());
     let receiver = channel.receiver();
@@ -364,7 +449,7 @@ fn setup_display_task(
 
     info!("Spawn tasks");
     spawner.must_spawn(update_display_task(
-        spi_device, busy, rst, dc, receiver, history,
+        display, receiver, history, splash, units, locale, statistics,
     ));
 
     Ok(sender)
@@ -412,6 +497,7 @@ fn setup_sensor_task(
 
 /// An error
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 enum Error {
     /// An impossible error existing only to satisfy the type system
     Impossible(Infallible),
@@ -420,22 +506,22 @@ enum Error {
     ParseCredentials,
 
     /// An error within WiFi operations
-    #[expect(unused, reason = "Never read directly")]
+    #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
     Wifi(WifiError),
 
     /// An error within clock operations
-    #[expect(unused, reason = "Never read directly")]
+    #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
     Clock(ClockError),
 
     /// An error within creation of DMA buffers
-    #[expect(unused, reason = "Never read directly")]
+    #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
     DmaBuffer(DmaBufError),
 
     /// An error within creation of SPI bus
     SpiConfig(SpiConfigError),
 
     /// An error within creation of I²C bus
-    #[expect(unused, reason = "Never read directly")]
+    #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
     I2cConfig(I2cConfigError),
 }
 
@@ -8,10 +8,6 @@
 
 //! Task for reading sensor value
 
-use log::error;
-use log::info;
-use log::warn;
-
 use embassy_time::Delay;
 use embassy_time::Duration;
 use embassy_time::Timer;
@@ -35,6 +31,9 @@
 use crate::domain::Error as DomainError;
 use crate::domain::Reading;
 use crate::domain::Sample;
+use crate::logging::error;
+use crate::logging::info;
+use crate::logging::warn;
 
 /// Interval to wait for sensor warmup
 const WARMUP_INTERVAL: Duration = Duration::from_millis(10);
@@ -52,7 +51,7 @@ pub async fn sample_task(
     let mut sensor = AsyncBme280::new(i2c, Delay);
 
     if let Err(error) = initialize(&mut sensor).await {
-        warn!("Could not initialize sensor: {error:?}");
+        warn!("Could not initialize sensor: {:?}", error);
     }
 
     info!(
@@ -63,7 +62,7 @@ pub async fn sample_task(
 
     loop {
         if let Err(error) = sample_and_send(&mut sensor, &mut rng, &sender, &clock).await {
-            error!("Could not sample sensor: {error:?}");
+            error!("Could not sample sensor: {:?}", error);
         }
 
         let wait_interval = clock.duration_to_next_rounded_wakeup(sampling_period);
@@ -89,7 +88,7 @@ async fn sample_and_send(
         .map_err(SensorError::I2c)
         .and_then(|sample: Bme280Sample| Ok(Sample::try_from(sample)?));
     let sample = sample_result.unwrap_or_else(|error| {
-        error!("Cannot read sample: {error:?}");
+        error!("Cannot read sample: {:?}", error);
         warn!("Use a random sample");
 
         Sample::random(rng)
@@ -121,15 +120,22 @@ async fn initialize(bme280: &mut AsyncBme280<I2c<'static, Async>, Delay>) -> Res
 
 /// Error within sensor sampling
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 enum SensorError {
     /// Error from clock
-    Clock(#[expect(unused, reason = "Never read directly")] ClockError),
+    Clock(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        ClockError,
+    ),
 
     /// Error from domain
     Domain(DomainError),
 
     /// Error from I²C bus
-    I2c(#[expect(unused, reason = "Never read directly")] I2cError),
+    I2c(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        I2cError,
+    ),
 }
 
 impl From<ClockError> for SensorError {
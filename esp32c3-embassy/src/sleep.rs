@@ -10,12 +10,12 @@
 
 use core::time::Duration;
 
-use log::info;
-
 use esp_hal::peripherals::LPWR;
 use esp_hal::rtc_cntl::sleep::TimerWakeupSource;
 use esp_hal::rtc_cntl::Rtc;
 
+use crate::logging::info;
+
 /// Enter deep sleep for the specified interval
 ///
 /// **NOTE**: WiFi must be turned off before entering deep sleep, otherwise
@@ -25,6 +25,6 @@ pub fn enter_deep(rtc_cntl: LPWR, interval: Duration) -> ! {
 
     let mut rtc = Rtc::new(rtc_cntl);
 
-    info!("Entering deep sleep for {interval:?}");
+    info!("Entering deep sleep for {:?}", interval);
     rtc.sleep_deep(&[&wakeup_source]);
 }
@@ -0,0 +1,28 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Splash screens stored in flash
+//!
+//! Splash screens are run-length encoded images, created with the `linux`
+//! example of the display driver, e.g.:
+//!
+//! ~~~~shell
+//! cargo run --target x86_64-unknown-linux-gnu --package waveshare-154bv2-rs \
+//!     --example linux --features std,draw-target -- \
+//!     --text '\n\n\n\n   ESP32-C3 Embassy\n\n\n\n     Starting...' \
+//!     --dry-run esp32c3-embassy/assets/boot.rle
+//! ~~~~
+//!
+//! They are decoded while being transferred to the display, so they do not
+//! need a buffer.
+
+use waveshare_154bv2_rs::CompressedImage;
+
+/// Splash screen shown on cold boot
+pub static BOOT: CompressedImage<'static> =
+    CompressedImage::new(include_bytes!("../assets/boot.rle"));
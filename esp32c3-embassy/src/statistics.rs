@@ -0,0 +1,186 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Statistics of a measurement over the history
+
+use heapless::HistoryBuf;
+
+use time::Duration;
+use time::OffsetDateTime;
+
+use uom::si::f32::Pressure;
+use uom::si::f32::Ratio as Humidity;
+use uom::si::f32::TemperatureInterval;
+use uom::si::pressure::hectopascal;
+use uom::si::ratio::percent;
+use uom::si::temperature_interval::degree_celsius;
+
+use crate::domain::Quantity;
+use crate::domain::Reading;
+
+/// Number of seconds in an hour
+const SECONDS_PER_HOUR: f32 = 3600.0;
+
+/// Configuration of statistics
+///
+/// Trend thresholds are changes over an hour, so they do not depend on the
+/// units used to present samples.
+#[derive(Clone, Copy, Debug)]
+pub struct StatisticsConfig {
+    /// Window before the most recent reading for minimal and maximal values
+    pub extremes_window: Duration,
+
+    /// Number of most recent readings used to compute the trend
+    pub trend_readings: usize,
+
+    /// Change over an hour above which temperature is rising
+    pub temperature_trend_threshold: TemperatureInterval,
+
+    /// Change over an hour above which humidity is rising
+    pub humidity_trend_threshold: Humidity,
+
+    /// Change over an hour above which pressure is rising
+    pub pressure_trend_threshold: Pressure,
+}
+
+impl StatisticsConfig {
+    /// Get the threshold on the rate of change for a quantity
+    ///
+    /// The threshold is expressed in the unit returned by
+    /// [`Quantity::value()`] per hour.
+    fn trend_threshold(self, quantity: Quantity) -> f32 {
+        match quantity {
+            Quantity::Temperature => self.temperature_trend_threshold.get::<degree_celsius>(),
+            Quantity::Humidity => self.humidity_trend_threshold.get::<percent>(),
+            Quantity::Pressure => self.pressure_trend_threshold.get::<hectopascal>(),
+        }
+    }
+}
+
+/// Direction in which a quantity is changing
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Trend {
+    /// The quantity is increasing
+    Rising,
+
+    /// The quantity is not changing significantly
+    Steady,
+
+    /// The quantity is decreasing
+    Falling,
+}
+
+impl Trend {
+    /// Classify a rate of change per hour
+    fn from_rate(rate: f32, threshold: f32) -> Self {
+        if rate >= threshold {
+            Self::Rising
+        } else if rate <= -threshold {
+            Self::Falling
+        } else {
+            Self::Steady
+        }
+    }
+}
+
+/// Statistics of a quantity over the history
+#[derive(Clone, Copy, Debug)]
+pub struct Statistics {
+    /// Minimal value within the window
+    pub minimum: f32,
+
+    /// Maximal value within the window
+    pub maximum: f32,
+
+    /// Trend over the most recent readings
+    pub trend: Trend,
+}
+
+impl Statistics {
+    /// Compute statistics of a quantity over the history
+    ///
+    /// Return `None` if the history is empty.
+    pub fn from_history<const SIZE: usize>(
+        history: &HistoryBuf<Reading, SIZE>,
+        quantity: Quantity,
+        config: StatisticsConfig,
+    ) -> Option<Self> {
+        let &(last_time, _) = history.recent()?;
+
+        let start = last_time - config.extremes_window;
+        let (minimum, maximum) = history.oldest_ordered().fold(
+            (f32::INFINITY, f32::NEG_INFINITY),
+            |(minimum, maximum), &(time, ref sample)| {
+                if time >= start {
+                    let value = quantity.value(sample);
+                    (minimum.min(value), maximum.max(value))
+                } else {
+                    (minimum, maximum)
+                }
+            },
+        );
+
+        let trend = rate(history, quantity, last_time, config.trend_readings)
+            .map_or(Trend::Steady, |rate| {
+                Trend::from_rate(rate, config.trend_threshold(quantity))
+            });
+
+        Some(Self {
+            minimum,
+            maximum,
+            trend,
+        })
+    }
+}
+
+/// Compute the rate of change per hour over the most recent readings
+///
+/// The rate is the slope of the least-squares line through the readings.
+/// Return `None` if all readings have the same time.
+fn rate<const SIZE: usize>(
+    history: &HistoryBuf<Reading, SIZE>,
+    quantity: Quantity,
+    last_time: OffsetDateTime,
+    readings: usize,
+) -> Option<f32> {
+    let skipped = history.len().saturating_sub(readings);
+    let points = || {
+        history
+            .oldest_ordered()
+            .skip(skipped)
+            .map(|&(time, ref sample)| {
+                let hours = (time - last_time).as_seconds_f32() / SECONDS_PER_HOUR;
+                (hours, quantity.value(sample))
+            })
+    };
+
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "The number of readings is small"
+    )]
+    let count = points().count() as f32;
+    let (sum_hours, sum_values) = points().fold(
+        (0.0_f32, 0.0_f32),
+        |(sum_hours, sum_values), (hours, value)| (sum_hours + hours, sum_values + value),
+    );
+    let mean_hours = sum_hours / count;
+    let mean_value = sum_values / count;
+
+    let (covariance, variance) = points().fold(
+        (0.0_f32, 0.0_f32),
+        |(covariance, variance), (hours, value)| {
+            let deviation = hours - mean_hours;
+            (
+                covariance + deviation * (value - mean_value),
+                variance + deviation * deviation,
+            )
+        },
+    );
+
+    (variance > 0.0).then(|| covariance / variance)
+}
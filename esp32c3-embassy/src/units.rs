@@ -0,0 +1,178 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Units of measurement for presenting samples
+//!
+//! Samples are stored as `uom` quantities, and are converted to the
+//! preferred units only when shown on the dashboard or written to log.
+
+#![allow(
+    dead_code,
+    reason = "Only the units selected in the firmware are constructed"
+)]
+
+use uom::si::f32::Pressure;
+use uom::si::f32::ThermodynamicTemperature as Temperature;
+use uom::si::pressure::hectopascal;
+use uom::si::pressure::inch_of_mercury;
+use uom::si::pressure::kilopascal;
+use uom::si::pressure::millimeter_of_mercury;
+use uom::si::thermodynamic_temperature::degree_celsius;
+use uom::si::thermodynamic_temperature::degree_fahrenheit;
+
+use crate::domain::Quantity;
+use crate::Sample;
+
+/// A unit for temperature
+#[derive(Clone, Copy, Debug)]
+pub enum TemperatureUnit {
+    /// Degrees Celsius
+    DegreeCelsius,
+
+    /// Degrees Fahrenheit
+    DegreeFahrenheit,
+}
+
+impl TemperatureUnit {
+    /// Get the value of a temperature in this unit
+    pub fn value(self, temperature: Temperature) -> f32 {
+        match self {
+            Self::DegreeCelsius => temperature.get::<degree_celsius>(),
+            Self::DegreeFahrenheit => temperature.get::<degree_fahrenheit>(),
+        }
+    }
+
+    /// Get the symbol of this unit
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Self::DegreeCelsius => "°C",
+            Self::DegreeFahrenheit => "°F",
+        }
+    }
+}
+
+/// A unit for pressure
+#[derive(Clone, Copy, Debug)]
+pub enum PressureUnit {
+    /// Hectopascals
+    Hectopascal,
+
+    /// Kilopascals
+    Kilopascal,
+
+    /// Inches of mercury
+    InchOfMercury,
+
+    /// Millimeters of mercury
+    MillimeterOfMercury,
+}
+
+impl PressureUnit {
+    /// Get the value of a pressure in this unit
+    pub fn value(self, pressure: Pressure) -> f32 {
+        match self {
+            Self::Hectopascal => pressure.get::<hectopascal>(),
+            Self::Kilopascal => pressure.get::<kilopascal>(),
+            Self::InchOfMercury => pressure.get::<inch_of_mercury>(),
+            Self::MillimeterOfMercury => pressure.get::<millimeter_of_mercury>(),
+        }
+    }
+
+    /// Get the symbol of this unit
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Self::Hectopascal => "hPa",
+            Self::Kilopascal => "kPa",
+            Self::InchOfMercury => "inHg",
+            Self::MillimeterOfMercury => "mmHg",
+        }
+    }
+
+    /// Get the number of decimal digits to show
+    ///
+    /// The resolution is roughly a tenth of hectopascal for all units.
+    pub const fn precision(self) -> usize {
+        match self {
+            Self::Hectopascal | Self::MillimeterOfMercury => 1,
+            Self::Kilopascal | Self::InchOfMercury => 2,
+        }
+    }
+}
+
+/// Preferred units for presenting samples
+///
+/// Units are either [`Units::METRIC`], [`Units::IMPERIAL`], or a custom
+/// combination created with [`Units::new()`].
+/// Humidity is always presented in percent.
+#[derive(Clone, Copy, Debug)]
+pub struct Units {
+    /// Unit for temperature
+    pub temperature: TemperatureUnit,
+
+    /// Unit for pressure
+    pub pressure: PressureUnit,
+}
+
+impl Units {
+    /// Metric units, i.e. degrees Celsius and hectopascals
+    pub const METRIC: Self = Self::new(TemperatureUnit::DegreeCelsius, PressureUnit::Hectopascal);
+
+    /// Imperial units, i.e. degrees Fahrenheit and inches of mercury
+    pub const IMPERIAL: Self = Self::new(
+        TemperatureUnit::DegreeFahrenheit,
+        PressureUnit::InchOfMercury,
+    );
+
+    /// Create a custom combination of units
+    pub const fn new(temperature: TemperatureUnit, pressure: PressureUnit) -> Self {
+        Self {
+            temperature,
+            pressure,
+        }
+    }
+
+    /// Get the value of a quantity in a sample in the preferred unit
+    pub fn value(self, quantity: Quantity, sample: &Sample) -> f32 {
+        match quantity {
+            Quantity::Temperature => self.temperature.value(sample.temperature),
+            Quantity::Humidity => quantity.value(sample),
+            Quantity::Pressure => self.pressure.value(sample.pressure),
+        }
+    }
+
+    /// Convert a value of a quantity to the preferred unit
+    ///
+    /// The value is expressed in the unit returned by [`Quantity::value()`].
+    pub fn convert(self, quantity: Quantity, value: f32) -> f32 {
+        match quantity {
+            Quantity::Temperature => self
+                .temperature
+                .value(Temperature::new::<degree_celsius>(value)),
+            Quantity::Humidity => value,
+            Quantity::Pressure => self.pressure.value(Pressure::new::<hectopascal>(value)),
+        }
+    }
+
+    /// Get the symbol of the preferred unit for a quantity
+    pub const fn symbol(self, quantity: Quantity) -> &'static str {
+        match quantity {
+            Quantity::Temperature => self.temperature.symbol(),
+            Quantity::Humidity => "%",
+            Quantity::Pressure => self.pressure.symbol(),
+        }
+    }
+
+    /// Get the number of decimal digits to show for a quantity
+    pub const fn precision(self, quantity: Quantity) -> usize {
+        match quantity {
+            Quantity::Temperature => 1,
+            Quantity::Humidity => 0,
+            Quantity::Pressure => self.pressure.precision(),
+        }
+    }
+}
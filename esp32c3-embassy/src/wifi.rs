@@ -10,10 +10,6 @@
 
 use alloc::string::ToString as _;
 
-use log::debug;
-use log::error;
-use log::info;
-
 use embassy_executor::Spawner;
 
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
@@ -52,6 +48,10 @@
 
 use rand_core::Rng as _;
 
+use crate::logging::debug;
+use crate::logging::error;
+use crate::logging::info;
+use crate::logging::Debug2Format;
 use crate::RngWrapper;
 
 /// Static cell for network stack resources
@@ -72,7 +72,7 @@ pub async fn connect(
 ) -> Result<Stack<'static>, Error> {
     let mut rng_wrapper = RngWrapper::from(rng);
     let seed = rng_wrapper.next_u64();
-    debug!("Use random seed 0x{seed:016x}");
+    debug!("Use random seed 0x{:016x}", seed);
 
     let radio_controller: &'static mut _ = RADIO_CONTROLLER.init(initialize_wifi()?);
 
@@ -120,7 +120,7 @@ async fn net_task(mut runner: Runner<'static, WifiDevice<'static>>) {
 #[embassy_executor::task]
 async fn connection(controller: WifiController<'static>, ssid: String<32>, password: String<64>) {
     if let Err(error) = connection_fallible(controller, ssid, password).await {
-        error!("Cannot connect to WiFi: {error:?}");
+        error!("Cannot connect to WiFi: {:?}", error);
     }
 }
 
@@ -131,7 +131,10 @@ async fn connection_fallible(
     password: String<64>,
 ) -> Result<(), Error> {
     debug!("Start connection");
-    debug!("Device capabilities: {:?}", controller.capabilities());
+    debug!(
+        "Device capabilities: {:?}",
+        Debug2Format(&controller.capabilities())
+    );
     loop {
         if let WifiStaState::Connected = sta_state() {
             // wait until we're no longer connected
@@ -165,7 +168,7 @@ async fn connection_fallible(
                 break;
             }
             Err(error) => {
-                error!("Failed to connect to WiFi network: {error:?}");
+                error!("Failed to connect to WiFi network: {:?}", error);
                 Timer::after(Duration::from_millis(5000)).await;
             }
         }
@@ -177,12 +180,19 @@ async fn connection_fallible(
 
 /// Error within WiFi connection
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error {
     /// Error during WiFi initialization
-    WifiInitialization(#[expect(unused, reason = "Never read directly")] WifiInitializationError),
+    WifiInitialization(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        WifiInitializationError,
+    ),
 
     /// Error during WiFi operation
-    Wifi(#[expect(unused, reason = "Never read directly")] EspWifiError),
+    Wifi(
+        #[cfg_attr(not(feature = "defmt"), expect(unused, reason = "Never read directly"))]
+        EspWifiError,
+    ),
 }
 
 impl From<WifiInitializationError> for Error {
@@ -0,0 +1,309 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Render text or an image on a display attached to a Linux single-board
+//! computer
+//!
+//! The display is connected through the kernel's SPI device and GPIO
+//! character device, with the pinout of the Waveshare Raspberry Pi HAT.
+//!
+//! ```text
+//! linux --text "Hello\nWorld"
+//! linux --image picture.bmp --rotation 90
+//! linux --text "Hello" --dry-run preview.png
+//! linux --image splash.bmp --dry-run splash.rle
+//! ```
+
+use std::env::args;
+use std::error::Error as StdError;
+use std::fs::read;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::exit;
+
+use embedded_graphics::geometry::Point;
+use embedded_graphics::image::Image;
+use embedded_graphics::mono_font::ascii::FONT_10X20;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Rgb888;
+use embedded_graphics::text::Baseline;
+use embedded_graphics::text::Text;
+use embedded_graphics::Drawable;
+
+use linux_embedded_hal::gpio_cdev::Chip;
+use linux_embedded_hal::gpio_cdev::LineRequestFlags;
+use linux_embedded_hal::spidev::SpiModeFlags;
+use linux_embedded_hal::spidev::SpidevOptions;
+use linux_embedded_hal::CdevPin;
+use linux_embedded_hal::Delay;
+use linux_embedded_hal::SpidevDevice;
+
+use tinybmp::Bmp;
+
+use waveshare_154bv2_rs::compress_buffer;
+use waveshare_154bv2_rs::write_png;
+use waveshare_154bv2_rs::write_ppm;
+use waveshare_154bv2_rs::Color;
+use waveshare_154bv2_rs::Display;
+use waveshare_154bv2_rs::DitheredImage;
+use waveshare_154bv2_rs::Dithering;
+use waveshare_154bv2_rs::Epd1in54Buffer;
+use waveshare_154bv2_rs::Epd1in54bV2;
+use waveshare_154bv2_rs::RefreshMode;
+use waveshare_154bv2_rs::Rotation;
+
+/// Default SPI device
+const DEFAULT_SPI_DEVICE: &str = "/dev/spidev0.0";
+
+/// Default GPIO character device
+const DEFAULT_GPIO_CHIP: &str = "/dev/gpiochip0";
+
+/// GPIO line of the busy pin
+const BUSY_LINE: u32 = 24;
+
+/// GPIO line of the reset pin
+const RST_LINE: u32 = 17;
+
+/// GPIO line of the DC pin
+const DC_LINE: u32 = 25;
+
+/// SPI clock frequency in hertz
+const SPI_FREQUENCY: u32 = 4_000_000;
+
+/// Label of requested GPIO lines
+const GPIO_CONSUMER: &str = "waveshare-154bv2-rs";
+
+/// Usage message
+const USAGE: &str = "\
+Usage: linux [OPTIONS] (--text TEXT | --image FILE)
+
+Options:
+    --text TEXT         Render text, with \"\\n\" separating lines
+    --image FILE        Render a BMP image with Floyd-Steinberg dithering
+    --rotation DEGREES  Rotate by 0, 90, 180 or 270 degrees [default: 0]
+    --dry-run FILE      Write a PNG, PPM or RLE image instead of using the display
+    --spi DEVICE        SPI device [default: /dev/spidev0.0]
+    --gpio-chip DEVICE  GPIO character device [default: /dev/gpiochip0]
+    --help              Print this message";
+
+/// Content to render
+enum Content {
+    /// Lines of text
+    Text(String),
+
+    /// A BMP image file
+    Image(PathBuf),
+}
+
+/// Command-line arguments
+struct Arguments {
+    /// Content to render
+    content: Content,
+
+    /// Buffer rotation
+    rotation: Rotation,
+
+    /// Image file to write instead of using the display
+    dry_run: Option<PathBuf>,
+
+    /// SPI device
+    spi: PathBuf,
+
+    /// GPIO character device
+    gpio_chip: PathBuf,
+}
+
+fn main() {
+    let arguments = match parse_arguments(args().skip(1)) {
+        Ok(Some(arguments)) => arguments,
+        Ok(None) => {
+            println!("{USAGE}");
+            return;
+        }
+        Err(error) => {
+            eprintln!("Error: {error}\n\n{USAGE}");
+            exit(2);
+        }
+    };
+
+    if let Err(error) = run(&arguments) {
+        eprintln!("Error: {error}");
+        exit(1);
+    }
+}
+
+/// Render the content, and show it on the display or write it to a file
+///
+/// # Errors
+///
+/// Returns an error if the content cannot be rendered, or if the display or
+/// the output file cannot be written
+fn run(arguments: &Arguments) -> Result<(), Box<dyn StdError>> {
+    let mut buffer = Epd1in54Buffer::new();
+    buffer.set_rotation(arguments.rotation);
+
+    match arguments.content {
+        Content::Text(ref text) => {
+            let style = MonoTextStyle::new(&FONT_10X20, Color::Black);
+            let Ok(_) =
+                Text::with_baseline(text, Point::new(4, 4), style, Baseline::Top).draw(&mut buffer);
+        }
+        Content::Image(ref path) => {
+            let data = read(path)?;
+            let bmp = Bmp::<Rgb888>::from_slice(&data)
+                .map_err(|error| format!("Cannot parse {}: {error:?}", path.display()))?;
+            let dithered: DitheredImage<_> = DitheredImage::new(&bmp, Dithering::FloydSteinberg);
+            let Ok(()) = Image::new(&dithered, Point::zero()).draw(&mut buffer);
+        }
+    }
+
+    if let Some(ref path) = arguments.dry_run {
+        write_image(&buffer, path)?;
+        println!("Buffer written to {}", path.display());
+    } else {
+        show(&buffer, arguments)?;
+    }
+
+    Ok(())
+}
+
+/// Write a buffer to an image file, in the format given by its extension
+///
+/// RLE images can be included in a firmware and drawn with
+/// `draw_compressed()`.
+///
+/// # Errors
+///
+/// Returns an error if the extension is not supported, or if the file cannot
+/// be written
+fn write_image(buffer: &Epd1in54Buffer, path: &Path) -> Result<(), Box<dyn StdError>> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    let writer = || File::create(path).map(BufWriter::new);
+
+    match extension.as_deref() {
+        Some("png") => write_png(buffer, writer()?)?,
+        Some("ppm") => write_ppm(buffer, writer()?)?,
+        Some("rle") => writer()?.write_all(&compress_buffer(buffer))?,
+        _ => return Err(format!("Unsupported image format: {}", path.display()).into()),
+    }
+
+    Ok(())
+}
+
+/// Show a buffer on the display
+///
+/// # Errors
+///
+/// Returns an error if the SPI device or the GPIO lines cannot be opened, or
+/// if any commands to the display fails
+fn show(buffer: &Epd1in54Buffer, arguments: &Arguments) -> Result<(), Box<dyn StdError>> {
+    let mut spi = SpidevDevice::open(&arguments.spi)?;
+    let options = SpidevOptions::new()
+        .bits_per_word(8)
+        .max_speed_hz(SPI_FREQUENCY)
+        .mode(SpiModeFlags::SPI_MODE_0)
+        .build();
+    spi.configure(&options)?;
+
+    let mut chip = Chip::new(&arguments.gpio_chip)?;
+    let busy = chip
+        .get_line(BUSY_LINE)?
+        .request(LineRequestFlags::INPUT, 0, GPIO_CONSUMER)?;
+    let rst = chip
+        .get_line(RST_LINE)?
+        .request(LineRequestFlags::OUTPUT, 1, GPIO_CONSUMER)?;
+    let dc = chip
+        .get_line(DC_LINE)?
+        .request(LineRequestFlags::OUTPUT, 0, GPIO_CONSUMER)?;
+
+    let mut display: Display<_, _, _, _, _, Epd1in54bV2> = Display::new(
+        spi,
+        CdevPin::new(busy)?,
+        CdevPin::new(rst)?,
+        CdevPin::new(dc)?,
+        Delay,
+    );
+
+    println!("Initialize display");
+    display.initialize()?;
+
+    println!("Draw buffer on display");
+    display.draw_buffer(buffer, RefreshMode::Full)?;
+
+    println!("Put display to sleep");
+    display.sleep()?;
+
+    Ok(())
+}
+
+/// Parse command-line arguments
+///
+/// Returns `None` if help was requested.
+///
+/// # Errors
+///
+/// Returns an error if arguments are missing, unknown or invalid
+fn parse_arguments(
+    mut arguments: impl Iterator<Item = String>,
+) -> Result<Option<Arguments>, String> {
+    let mut content = None;
+    let mut rotation = Rotation::Rotate0;
+    let mut dry_run = None;
+    let mut spi = PathBuf::from(DEFAULT_SPI_DEVICE);
+    let mut gpio_chip = PathBuf::from(DEFAULT_GPIO_CHIP);
+
+    while let Some(argument) = arguments.next() {
+        let mut value = || {
+            arguments
+                .next()
+                .ok_or_else(|| format!("Missing value for {argument}"))
+        };
+
+        match argument.as_str() {
+            "--text" => content = Some(Content::Text(value()?.replace("\\n", "\n"))),
+            "--image" => content = Some(Content::Image(value()?.into())),
+            "--rotation" => rotation = parse_rotation(&value()?)?,
+            "--dry-run" => dry_run = Some(value()?.into()),
+            "--spi" => spi = value()?.into(),
+            "--gpio-chip" => gpio_chip = value()?.into(),
+            "--help" | "-h" => return Ok(None),
+            _ => return Err(format!("Unknown argument {argument}")),
+        }
+    }
+
+    let content = content.ok_or("Either --text or --image is required")?;
+
+    Ok(Some(Arguments {
+        content,
+        rotation,
+        dry_run,
+        spi,
+        gpio_chip,
+    }))
+}
+
+/// Parse a rotation in degrees
+///
+/// # Errors
+///
+/// Returns an error if the rotation is not a multiple of 90 degrees
+fn parse_rotation(degrees: &str) -> Result<Rotation, String> {
+    match degrees {
+        "0" => Ok(Rotation::Rotate0),
+        "90" => Ok(Rotation::Rotate90),
+        "180" => Ok(Rotation::Rotate180),
+        "270" => Ok(Rotation::Rotate270),
+        _ => Err(format!("Invalid rotation {degrees}")),
+    }
+}
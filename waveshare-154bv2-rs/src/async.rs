@@ -8,37 +8,84 @@
 
 //! Async display
 
+use core::future::poll_fn;
+use core::future::Future;
+use core::pin::pin;
+use core::task::Poll;
+use core::time::Duration;
+
+#[cfg(feature = "draw-target")]
+use core::convert::Infallible;
+
+#[cfg(feature = "defmt")]
+use defmt::debug;
+#[cfg(feature = "defmt")]
+use defmt::trace;
+#[cfg(feature = "defmt")]
+use defmt::warn;
+
+#[cfg(not(feature = "defmt"))]
 use log::debug;
-use log::log_enabled;
+#[cfg(not(feature = "defmt"))]
 use log::trace;
-use log::Level::Trace;
+#[cfg(not(feature = "defmt"))]
+use log::warn;
 
 use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::digital::Wait;
 use embedded_hal_async::spi::SpiDevice;
 
+use embedded_hal::digital::InputPin;
 use embedded_hal::digital::OutputPin;
 
-use crate::command;
+use crate::protocol::Encoding;
+use crate::protocol::Operation;
+use crate::protocol::Protocol;
+use crate::rle::Decoder;
+use crate::CompressedImage;
+use crate::DisplayConfig;
+use crate::Epd1in54bV2;
 use crate::Error;
+use crate::Panel;
+use crate::PowerState;
+use crate::RefreshMode;
+use crate::WriteStrategy;
+
+#[cfg(feature = "draw-target")]
+use embedded_graphics_core::draw_target::DrawTarget;
+#[cfg(feature = "draw-target")]
+use embedded_graphics_core::geometry::OriginDimensions;
+#[cfg(feature = "draw-target")]
+use embedded_graphics_core::geometry::Size;
+#[cfg(feature = "draw-target")]
+use embedded_graphics_core::primitives::Rectangle;
+#[cfg(feature = "draw-target")]
+use embedded_graphics_core::Pixel;
 
 #[cfg(feature = "draw-target")]
 use crate::Buffer;
+#[cfg(feature = "draw-target")]
+use crate::Color;
+#[cfg(feature = "draw-target")]
+use crate::Frame;
+#[cfg(feature = "draw-target")]
+use crate::Strip;
 
 /// Flag for busy low
 const IS_BUSY_LOW: bool = false;
 
-/// Display width
-const WIDTH: usize = 200;
-
-/// Display height
-const HEIGHT: usize = 200;
-
-/// Display byte size
-const BYTE_SIZE: usize = 5000;
+/// Size of chunks used when encoding data
+const ENCODING_CHUNK_SIZE: usize = 64;
 
 /// A Waveshare E-ink screen
-pub struct Display<SPI: SpiDevice, BUSY: Wait, RST: OutputPin, DC: OutputPin, DELAY: DelayNs> {
+pub struct Display<
+    SPI: SpiDevice,
+    BUSY: Wait,
+    RST: OutputPin,
+    DC: OutputPin,
+    DELAY: DelayNs,
+    PANEL: Panel = Epd1in54bV2,
+> {
     /// SPI interface
     spi: SPI,
 
@@ -54,19 +101,32 @@ pub struct Display<SPI: SpiDevice, BUSY: Wait, RST: OutputPin, DC: OutputPin, DE
     ///Delay
     delay: DELAY,
 
-    /// Flag to force writing one byte at the time
-    individual_writes: bool,
+    /// Maximal time to wait for the display to become idle
+    busy_timeout: Option<Duration>,
+
+    /// Flag to reset and initialize the display after a busy timeout
+    recover_on_busy_timeout: bool,
+
+    /// Display protocol
+    protocol: Protocol<PANEL>,
+
+    /// Strategy for writing to SPI bus
+    write_strategy: WriteStrategy,
 }
 
-impl<SPI, BUSY, RST, DC, DELAY> Display<SPI, BUSY, RST, DC, DELAY>
+impl<SPI, BUSY, RST, DC, DELAY, PANEL> Display<SPI, BUSY, RST, DC, DELAY, PANEL>
 where
     SPI: SpiDevice,
     BUSY: Wait,
     RST: OutputPin,
     DC: OutputPin,
     DELAY: DelayNs,
+    PANEL: Panel,
 {
     /// Create a new display
+    ///
+    /// The panel must be specified when it cannot be inferred, e.g.
+    /// `Display::<_, _, _, _, _, Epd2in9bV4>::new(...)`.
     #[must_use]
     pub fn new(spi: SPI, busy: BUSY, rst: RST, dc: DC, delay: DELAY) -> Self {
         Self {
@@ -75,11 +135,15 @@ pub fn new(spi: SPI, busy: BUSY, rst: RST, dc: DC, delay: DELAY) -> Self {
             rst,
             dc,
             delay,
-            individual_writes: false,
+            busy_timeout: None,
+            recover_on_busy_timeout: false,
+            protocol: Protocol::new(),
+            write_strategy: WriteStrategy::default(),
         }
     }
 
     /// Create a new display, writing individual bytes to SPI
+    #[deprecated(note = "use `with_write_strategy(WriteStrategy::PerByte)` instead")]
     #[must_use]
     pub fn new_with_individual_writes(
         spi: SPI,
@@ -88,14 +152,48 @@ pub fn new_with_individual_writes(
         dc: DC,
         delay: DELAY,
     ) -> Self {
-        Self {
-            spi,
-            busy,
-            rst,
-            dc,
-            delay,
-            individual_writes: true,
-        }
+        Self::new(spi, busy, rst, dc, delay).with_write_strategy(WriteStrategy::PerByte)
+    }
+
+    /// Set the strategy for writing to SPI bus
+    ///
+    /// By default data is written in bulk, or in chunks of 4096 bytes on
+    /// Linux.
+    #[must_use]
+    pub fn with_write_strategy(mut self, write_strategy: WriteStrategy) -> Self {
+        self.write_strategy = write_strategy;
+        self
+    }
+
+    /// Set the configuration of the display controller
+    ///
+    /// The configuration is applied by [`Self::initialize()`].
+    /// By default the panel's own settings are used.
+    #[must_use]
+    pub fn with_config(mut self, config: DisplayConfig) -> Self {
+        self.protocol.set_config(config);
+        self
+    }
+
+    /// Set a timeout for waiting on the busy pin
+    ///
+    /// By default the driver waits forever for the display to become idle.
+    /// With a timeout, operations return [`Error::BusyTimeout`] if the display
+    /// is still busy when it expires.
+    #[must_use]
+    pub fn with_busy_timeout(mut self, timeout: Duration) -> Self {
+        self.busy_timeout = Some(timeout);
+        self
+    }
+
+    /// Reset and initialize the display after a busy timeout during refresh
+    ///
+    /// The refresh still returns [`Error::BusyTimeout`], but the display is
+    /// ready to accept new commands afterwards.
+    #[must_use]
+    pub fn with_recovery_on_busy_timeout(mut self, recover: bool) -> Self {
+        self.recover_on_busy_timeout = recover;
+        self
     }
 
     /// Initialize display
@@ -105,109 +203,94 @@ pub fn new_with_individual_writes(
     /// Returns an error if any commands to the display fails
     pub async fn initialize(&mut self) -> Result<(), Error> {
         debug!("Initialize display");
-
-        self.hardware_reset().await?;
-        self.software_reset().await?;
-        self.set_driver_output_control().await?;
-        self.set_ram_size(WIDTH, HEIGHT).await?;
-        self.set_border_waveform_control().await?;
-        self.set_ram_address_counters().await?;
-
-        self.wait_until_idle().await?;
+        let operations = self.protocol.initialize();
+        self.execute(operations).await?;
+        self.protocol.complete_initialize();
         debug!("Initialize display / Done");
 
         Ok(())
     }
 
-    /// Set RAM address counters
+    /// Return the current power state
+    #[must_use]
+    pub fn power_state(&self) -> PowerState {
+        self.protocol.power_state()
+    }
+
+    /// Put the display in deep sleep
+    ///
+    /// The display retains its image, but it does not accept drawing commands
+    /// until it is woken up.
     ///
     /// # Errors
     ///
     /// Returns an error if any commands to the display fails
-    async fn set_ram_address_counters(&mut self) -> Result<(), Error> {
-        debug!("Set RAM address counters");
-        self.send_command(command::SET_RAM_X_ADDRESS_COUNTER)
-            .await?;
-        self.send_data(&[0x00]).await?;
-        self.send_command(command::SET_RAM_Y_ADDRESS_COUNTER)
-            .await?;
-        self.send_data(&[0xc7]).await?;
-        self.send_data(&[0x00]).await?;
-        debug!("Set RAM address counters / done");
+    pub async fn sleep(&mut self) -> Result<(), Error> {
+        let Some(operations) = self.protocol.sleep() else {
+            return Ok(());
+        };
+
+        debug!("Enter deep sleep");
+        self.execute(operations).await?;
+        self.protocol.complete_sleep();
+        debug!("Enter deep sleep / Done");
 
         Ok(())
     }
 
-    /// Set border waveform control
+    /// Wake the display from deep sleep
+    ///
+    /// Deep sleep can only be exited through a hardware reset, so the display
+    /// is reset and initialized again.
+    /// Nothing happens if the display is already awake.
     ///
     /// # Errors
     ///
     /// Returns an error if any commands to the display fails
-    async fn set_border_waveform_control(&mut self) -> Result<(), Error> {
-        debug!("Set border waveform control");
-        self.send_command(command::BORDER_WAVEFORM_CONTROL).await?;
-        self.send_data(&[0x05]).await?;
-        debug!("Set border waveform control / done");
+    pub async fn wake(&mut self) -> Result<(), Error> {
+        if self.protocol.power_state() == PowerState::Awake {
+            return Ok(());
+        }
 
-        Ok(())
+        debug!("Wake display");
+        self.initialize().await
     }
 
-    /// Set driver output control
+    /// Set the temperature used to select the refresh waveform
+    ///
+    /// By default the display measures temperature with its own sensor before
+    /// every refresh.
+    /// After calling this function, the given temperature is used instead,
+    /// until the display is initialized again.
     ///
     /// # Errors
     ///
     /// Returns an error if any commands to the display fails
-    async fn set_driver_output_control(&mut self) -> Result<(), Error> {
-        debug!("Set driver output control");
-        self.wait_until_idle().await?;
-        self.send_command(command::DRIVER_OUTPUT_CONTROL).await?;
-        self.send_data(&[0xc7, 0x00, 0x01]).await?;
-        debug!("Set driver output control / done");
+    pub async fn set_temperature(&mut self, celsius: f32) -> Result<(), Error> {
+        debug!("Set temperature");
+        let operations = self.protocol.set_temperature(celsius)?;
+        self.execute(operations).await?;
+        self.protocol.complete_set_temperature(celsius);
+        debug!("Set temperature / Done");
 
         Ok(())
     }
 
-    /// Set RAM size
+    /// Load a custom waveform LUT
+    ///
+    /// The LUT is used for all following refreshes instead of the one stored
+    /// in the display, until the display is initialized again.
+    /// Its format and size depend on the display controller.
     ///
     /// # Errors
     ///
     /// Returns an error if any commands to the display fails
-    #[allow(clippy::cast_possible_truncation, clippy::panic_in_result_fn)]
-    async fn set_ram_size(&mut self, width: usize, height: usize) -> Result<(), Error> {
-        debug!("Set RAM size");
-        self.send_command(command::DATA_ENTRY_MODE).await?;
-        self.send_data(&[0x01]).await?;
-
-        let x_start = 0;
-        let x_end = width as u8 / 8 - 1;
-
-        assert_eq!(x_start, 0x00);
-        assert_eq!(x_end, 0x18); // 0x18 = 24 = 200 / 8 - 1
-
-        self.send_command(command::SET_RAM_X_ADDRESS_START_END_POSITION)
-            .await?;
-        self.send_data(&[x_start, x_end]).await?;
-
-        let y_start = height as u16 - 1;
-        let y_end = 0_u16;
-
-        assert_eq!(y_start, 0xc7);
-        assert_eq!(y_end, 0x00);
-
-        let [y_start_0, y_start_1] = y_start.to_le_bytes();
-        let [y_end_0, y_end_1] = y_end.to_le_bytes();
-
-        assert_eq!(y_start_0, 0xc7); // 0xC7 = 199 = 200 - 1
-        assert_eq!(y_start_1, 0x00);
-
-        assert_eq!(y_end_0, 0x00);
-        assert_eq!(y_end_1, 0x00);
-
-        self.send_command(command::SET_RAM_Y_ADDRESS_START_END_POSITION)
-            .await?;
-        self.send_data(&[y_start_0, y_start_1, y_end_0, y_end_1])
-            .await?;
-        debug!("Set RAM size / done");
+    pub async fn load_lut(&mut self, lut: &[u8]) -> Result<(), Error> {
+        debug!("Load LUT");
+        let operations = self.protocol.load_lut(lut)?;
+        self.execute(operations).await?;
+        self.protocol.complete_load_lut();
+        debug!("Load LUT / Done");
 
         Ok(())
     }
@@ -219,49 +302,196 @@ async fn set_ram_size(&mut self, width: usize, height: usize) -> Result<(), Erro
     /// Returns an error if any commands to the display fails
     pub async fn clear(&mut self) -> Result<(), Error> {
         debug!("Clear display");
-        let linewidth = WIDTH / 8;
+        let operations = self.protocol.clear()?;
+        self.execute_refresh(operations, RefreshMode::Full).await?;
+        debug!("Clear display / Done");
 
-        self.send_command(command::WRITE_RAM_BLACK).await?;
-        for _ in 0..linewidth {
-            for _ in 0..HEIGHT {
-                self.send_data(&[0xff]).await?;
-            }
-        }
+        Ok(())
+    }
 
-        self.send_command(command::WRITE_RAM_CHROMATIC).await?;
-        for _ in 0..linewidth {
-            for _ in 0..HEIGHT {
-                self.send_data(&[0x00]).await?;
-            }
-        }
+    #[cfg(feature = "draw-target")]
+    /// Check whether the display already shows the content of a buffer,
+    /// according to the last frame sent to it
+    ///
+    /// In that case [`Self::draw_buffer_changes()`] does nothing, so the
+    /// display does not need to be woken up.
+    /// With [`RefreshMode::Fast`] the chromatic plane is not compared.
+    #[must_use]
+    pub fn is_up_to_date<const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize>(
+        &self,
+        buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+        frame: &Frame<PANEL>,
+        mode: RefreshMode,
+    ) -> bool {
+        self.protocol.is_up_to_date(buffer, frame, mode)
+    }
 
-        self.refresh().await?;
-        debug!("Clear display / Done");
+    #[cfg(feature = "draw-target")]
+    /// Update the display with the content of a buffer
+    ///
+    /// Whole planes are transferred.
+    /// With [`RefreshMode::Fast`] the chromatic plane is not transferred.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer does not match the panel, or if any
+    /// commands to the display fails
+    pub async fn draw_buffer<const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize>(
+        &mut self,
+        buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+        mode: RefreshMode,
+    ) -> Result<(), Error> {
+        self.update_buffer(buffer, None, mode).await
+    }
+
+    #[cfg(feature = "draw-target")]
+    /// Update the display with the changes of a buffer since the last frame
+    ///
+    /// The frame is owned by the caller and it is updated after the refresh.
+    /// If the buffer has the same content, nothing is transferred and the
+    /// display is not refreshed.
+    /// Otherwise, only the smallest area of each plane containing all changes
+    /// is transferred, unless display RAM was lost when the display was reset.
+    ///
+    /// With [`RefreshMode::Fast`] the chromatic plane is not transferred.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer does not match the panel, or if any
+    /// commands to the display fails
+    pub async fn draw_buffer_changes<
+        const WIDTH: usize,
+        const HEIGHT: usize,
+        const BYTE_SIZE: usize,
+    >(
+        &mut self,
+        buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+        frame: &mut Frame<PANEL>,
+        mode: RefreshMode,
+    ) -> Result<(), Error> {
+        self.update_buffer(buffer, Some(frame), mode).await
+    }
 
+    #[cfg(feature = "draw-target")]
+    /// Update the display with the content of a buffer, optionally comparing
+    /// it against the last frame
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer does not match the panel, or if any
+    /// commands to the display fails
+    async fn update_buffer<const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize>(
+        &mut self,
+        buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+        frame: Option<&mut Frame<PANEL>>,
+        mode: RefreshMode,
+    ) -> Result<(), Error> {
+        debug!("Update display");
+        let Some(operations) = self.protocol.draw_buffer(buffer, frame.as_deref(), mode)? else {
+            debug!("Update display / Unchanged");
+            return Ok(());
+        };
+
+        self.execute_refresh(operations, mode).await?;
+        if let Some(frame) = frame {
+            self.protocol.complete_draw_buffer(buffer, frame, mode);
+        }
+        debug!("Update display / Done");
         Ok(())
     }
 
     #[cfg(feature = "draw-target")]
+    /// Update a rectangular area of the display
     ///
+    /// The area is expressed in the buffer's rotated coordinates.
+    /// Only the bytes of each plane covering the area are transferred, and the
+    /// display is refreshed with the given mode, usually
+    /// [`RefreshMode::Partial`].
+    /// Since RAM is addressed in bytes, the area is widened horizontally to
+    /// the nearest multiples of 8 pixels.
+    ///
+    /// With [`RefreshMode::Fast`] the chromatic plane is not transferred, and
+    /// frames are no longer trusted.
     ///
     /// # Errors
     ///
-    /// Returns an error if any commands to the display fails
-    pub async fn draw_buffer(
+    /// Returns an error if the buffer does not match the panel, or if any
+    /// commands to the display fails
+    pub async fn draw_buffer_window<
+        const WIDTH: usize,
+        const HEIGHT: usize,
+        const BYTE_SIZE: usize,
+    >(
         &mut self,
         buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+        area: &Rectangle,
+        mode: RefreshMode,
     ) -> Result<(), Error> {
-        debug!("Update display");
+        debug!("Update display window");
+        let Some(operations) = self.protocol.draw_buffer_window(buffer, area, mode)? else {
+            debug!("Update display window / Empty");
+            return Ok(());
+        };
+
+        self.execute_refresh(operations, mode).await?;
+        debug!("Update display window / Done");
+        Ok(())
+    }
 
-        self.transfer_black(buffer.black_buffer()).await?;
-        self.transfer_chromatic(buffer.chromatic_buffer()).await?;
+    #[cfg(feature = "draw-target")]
+    /// Update the display by drawing it strip by strip
+    ///
+    /// The strip is moved from the top to the bottom of the screen.
+    /// At each position it is cleared, passed to `draw`, and transferred to
+    /// the corresponding rows of display RAM.
+    /// Only the strip is kept in memory, so `draw` must draw the whole screen
+    /// every time, and frames are no longer trusted.
+    ///
+    /// With [`RefreshMode::Fast`] the chromatic plane is not transferred.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the strip does not match the panel, if `draw`
+    /// fails, or if any commands to the display fails
+    pub async fn draw_strips<
+        const WIDTH: usize,
+        const HEIGHT: usize,
+        const ROWS: usize,
+        const BYTE_SIZE: usize,
+        E,
+        F,
+    >(
+        &mut self,
+        strip: &mut Strip<WIDTH, HEIGHT, ROWS, BYTE_SIZE>,
+        mode: RefreshMode,
+        mut draw: F,
+    ) -> Result<(), E>
+    where
+        E: From<Error>,
+        F: FnMut(&mut Strip<WIDTH, HEIGHT, ROWS, BYTE_SIZE>) -> Result<(), E>,
+    {
+        debug!("Update display in strips");
+        self.protocol
+            .draw_strips::<WIDTH, HEIGHT, ROWS, BYTE_SIZE>()?;
+
+        for first_row in (0..PANEL::HEIGHT).step_by(ROWS) {
+            strip.move_to(first_row);
+            draw(strip)?;
+
+            trace!("Transfer strip");
+            self.execute(self.protocol.transfer_strip(strip, mode))
+                .await?;
+        }
 
-        self.refresh().await?;
-        debug!("Update display / Done");
+        let operations = self.protocol.refresh_strips(mode);
+        self.execute_refresh(operations, mode).await?;
+        debug!("Update display in strips / Done");
         Ok(())
     }
 
+    /// Update the display with raw color planes
     ///
+    /// With [`RefreshMode::Fast`] the chromatic plane is not transferred.
     ///
     /// # Errors
     ///
@@ -270,18 +500,11 @@ pub async fn transfer_channels(
         &mut self,
         black: Option<&[u8]>,
         chromatic: Option<&[u8]>,
+        mode: RefreshMode,
     ) -> Result<(), Error> {
         debug!("Update display");
-
-        if let Some(black) = black {
-            self.transfer_black(black).await?;
-        }
-
-        if let Some(chromatic) = chromatic {
-            self.transfer_chromatic(chromatic).await?;
-        }
-
-        self.refresh().await?;
+        let operations = self.protocol.transfer_channels(black, chromatic, mode)?;
+        self.execute_refresh(operations, mode).await?;
         debug!("Update display / Done");
         Ok(())
     }
@@ -292,20 +515,9 @@ pub async fn transfer_channels(
     ///
     /// Returns an error if any commands to the display fails
     pub async fn transfer_chromatic(&mut self, chromatic: &[u8]) -> Result<(), Error> {
-        /// Line width
-        const LINEWIDTH: usize = WIDTH / 8;
-
         debug!("Transfer chromatic data");
-        self.send_command(command::WRITE_RAM_CHROMATIC).await?;
-
-        trace!("Compute inverse of chromatic data");
-        let mut buffer = [0x00; (HEIGHT * LINEWIDTH)];
-        for (byte, chromatic) in &mut buffer.iter_mut().zip(chromatic.iter()) {
-            *byte = !chromatic;
-        }
-        self.send_data(&buffer).await?;
-
-        Ok(())
+        let operations = self.protocol.transfer_chromatic(chromatic)?;
+        self.execute(operations).await
     }
 
     ///
@@ -315,12 +527,143 @@ pub async fn transfer_chromatic(&mut self, chromatic: &[u8]) -> Result<(), Error
     /// Returns an error if any commands to the display fails
     pub async fn transfer_black(&mut self, black: &[u8]) -> Result<(), Error> {
         debug!("Transfer black data");
-        self.send_command(command::WRITE_RAM_BLACK).await?;
-        self.send_data(black).await?;
+        let operations = self.protocol.transfer_black(black)?;
+        self.execute(operations).await
+    }
 
+    /// Update the display with a compressed image
+    ///
+    /// The image is decoded while it is transferred, so it is never stored
+    /// whole in memory.
+    /// Frames are no longer trusted afterwards.
+    ///
+    /// With [`RefreshMode::Fast`] the chromatic plane is not transferred.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image is malformed or does not match the
+    /// panel, or if any commands to the display fails
+    pub async fn draw_compressed(
+        &mut self,
+        image: &CompressedImage<'_>,
+        mode: RefreshMode,
+    ) -> Result<(), Error> {
+        debug!("Draw compressed image");
+        let operations = self.protocol.draw_compressed(image, mode)?;
+        self.execute_refresh(operations, mode).await?;
+        debug!("Draw compressed image / Done");
         Ok(())
     }
 
+    /// Start refreshing the display with the content of its RAM
+    ///
+    /// Unlike the other functions, this one does not wait for the refresh to
+    /// complete.
+    /// The returned handle borrows the display, so nothing can be sent to it
+    /// until the refresh is complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any commands to the display fails
+    pub async fn start_refresh(
+        &mut self,
+        mode: RefreshMode,
+    ) -> Result<RefreshHandle<'_, SPI, BUSY, RST, DC, DELAY, PANEL>, Error> {
+        debug!("Start refresh");
+        let operations = self.protocol.start_refresh(mode)?;
+        self.execute(operations).await?;
+        self.protocol.complete_start_refresh(mode);
+
+        Ok(RefreshHandle { display: self })
+    }
+
+    #[cfg(feature = "draw-target")]
+    /// Update the display with the content of a buffer, without waiting for
+    /// the refresh to complete
+    ///
+    /// This works like [`Self::draw_buffer()`], but it returns a handle to
+    /// the refresh as [`Self::start_refresh()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer does not match the panel, or if any
+    /// commands to the display fails
+    pub async fn start_draw_buffer<
+        const WIDTH: usize,
+        const HEIGHT: usize,
+        const BYTE_SIZE: usize,
+    >(
+        &mut self,
+        buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+        mode: RefreshMode,
+    ) -> Result<RefreshHandle<'_, SPI, BUSY, RST, DC, DELAY, PANEL>, Error> {
+        self.start_update_buffer(buffer, None, mode).await?;
+        Ok(RefreshHandle { display: self })
+    }
+
+    #[cfg(feature = "draw-target")]
+    /// Update the display with the changes of a buffer since the last frame,
+    /// without waiting for the refresh to complete
+    ///
+    /// This works like [`Self::draw_buffer_changes()`], but it returns a
+    /// handle to the refresh as [`Self::start_refresh()`].
+    /// Return `None` if the display already shows the buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer does not match the panel, or if any
+    /// commands to the display fails
+    #[allow(clippy::type_complexity)]
+    pub async fn start_draw_buffer_changes<
+        const WIDTH: usize,
+        const HEIGHT: usize,
+        const BYTE_SIZE: usize,
+    >(
+        &mut self,
+        buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+        frame: &mut Frame<PANEL>,
+        mode: RefreshMode,
+    ) -> Result<Option<RefreshHandle<'_, SPI, BUSY, RST, DC, DELAY, PANEL>>, Error> {
+        let started = self.start_update_buffer(buffer, Some(frame), mode).await?;
+        Ok(started.then_some(RefreshHandle { display: self }))
+    }
+
+    #[cfg(feature = "draw-target")]
+    /// Update the display with the content of a buffer, optionally comparing
+    /// it against the last frame, without waiting for the refresh to complete
+    ///
+    /// Return whether a refresh was started.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer does not match the panel, or if any
+    /// commands to the display fails
+    async fn start_update_buffer<
+        const WIDTH: usize,
+        const HEIGHT: usize,
+        const BYTE_SIZE: usize,
+    >(
+        &mut self,
+        buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+        frame: Option<&mut Frame<PANEL>>,
+        mode: RefreshMode,
+    ) -> Result<bool, Error> {
+        debug!("Start update display");
+        let Some(operations) = self.protocol.draw_buffer(buffer, frame.as_deref(), mode)? else {
+            debug!("Start update display / Unchanged");
+            return Ok(false);
+        };
+
+        self.execute(operations).await?;
+        self.protocol.complete_start_refresh(mode);
+        if let Some(frame) = frame {
+            self.protocol.complete_draw_buffer(buffer, frame, mode);
+        }
+        debug!("Start update display / Done");
+
+        Ok(true)
+    }
+
     /// Release display and return inner hardware
     ///
     /// # Errors
@@ -328,48 +671,125 @@ pub async fn transfer_black(&mut self, black: &[u8]) -> Result<(), Error> {
     /// Returns an error if any commands to the display fails
     pub async fn release(mut self) -> Result<(SPI, BUSY, RST, DC), Error> {
         debug!("Release display");
-        self.send_command(command::DEEP_SLEEP_MODE).await?;
-        self.send_data(&[0x01]).await?;
-
-        self.delay.delay_ms(200).await;
+        self.sleep().await?;
         debug!("Release display / Done");
 
         Ok((self.spi, self.busy, self.rst, self.dc))
     }
 
-    /// Refresh the display
+    /// Execute operations ending with a refresh of the display, and wait for
+    /// the refresh to complete
     ///
     /// # Errors
     ///
     /// Returns an error if any commands to the display fails
-    async fn refresh(&mut self) -> Result<(), Error> {
-        debug!("Refresh display");
-        self.send_command(command::DISPLAY_UPDATE_CONTROL_2).await?;
-        self.send_data(&[0xf7]).await?;
+    async fn execute_refresh<'data>(
+        &mut self,
+        operations: impl IntoIterator<Item = Operation<'data>>,
+        mode: RefreshMode,
+    ) -> Result<(), Error> {
+        self.execute(operations).await?;
+        self.protocol.complete_start_refresh(mode);
+        self.wait_for_refresh().await
+    }
 
-        self.send_command(command::MASTER_ACTIVATION).await?;
+    /// Wait for a started refresh to complete
+    ///
+    /// If the display is stuck during the refresh and recovery is enabled, it
+    /// is reset and initialized again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any commands to the display fails
+    async fn wait_for_refresh(&mut self) -> Result<(), Error> {
+        match self.finish_refresh().await {
+            Err(Error::BusyTimeout) if self.recover_on_busy_timeout => {
+                warn!("Display is stuck, reset and initialize it");
+                self.initialize().await?;
+                Err(Error::BusyTimeout)
+            }
+            result => result,
+        }
+    }
 
-        self.wait_until_idle().await?;
+    /// Execute operations on the display hardware
+    ///
+    /// A refresh started earlier is finished first, so that nothing is sent
+    /// to the display while it is refreshing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any commands to the display fails
+    async fn execute<'data>(
+        &mut self,
+        operations: impl IntoIterator<Item = Operation<'data>>,
+    ) -> Result<(), Error> {
+        self.finish_refresh().await?;
+        for operation in operations {
+            self.execute_operation(operation).await?;
+        }
+
+        Ok(())
+    }
 
-        debug!("Refresh display / Done");
+    /// Finish a started refresh, if any
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any commands to the display fails
+    async fn finish_refresh(&mut self) -> Result<(), Error> {
+        if let Some(operations) = self.protocol.finish_refresh() {
+            trace!("Wait for refresh to complete");
+            for operation in operations {
+                self.execute_operation(operation).await?;
+            }
+        }
 
         Ok(())
     }
 
-    /// Send a reset command to the display
+    /// Execute an operation on the display hardware
     ///
     /// # Errors
     ///
     /// Returns an error if any commands to the display fails
-    async fn software_reset(&mut self) -> Result<(), Error> {
-        debug!("Software reset");
-        self.wait_until_idle().await?;
-        self.send_command(command::SOFTWARE_RESET).await?;
-        debug!("Software reset / done");
+    async fn execute_operation(&mut self, operation: Operation<'_>) -> Result<(), Error> {
+        match operation {
+            Operation::Reset(high) => self.set_reset(high)?,
+            Operation::Delay(milliseconds) => self.delay.delay_ms(milliseconds).await,
+            Operation::WaitUntilIdle => self.wait_until_idle().await?,
+            Operation::Command(command) => self.send_command(command).await?,
+            Operation::Parameters(parameters) => {
+                self.send_data(parameters.as_slice()).await?;
+            }
+            Operation::Data(data) => self.send_data(data).await?,
+            Operation::Fill(byte, length) => self.send_filled_data(byte, length).await?,
+            Operation::EncodedData(data, encoding) => {
+                self.send_encoded_data(data, encoding).await?;
+            }
+            Operation::CompressedData(data, encoding) => {
+                self.send_compressed_data(data, encoding).await?;
+            }
+        }
 
         Ok(())
     }
 
+    /// Set the reset pin
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if setting the pin fails.
+    fn set_reset(&mut self, high: bool) -> Result<(), Error> {
+        if high {
+            trace!("Set RST high");
+            self.rst.set_high().map_err(Error::from_digital)
+        } else {
+            trace!("Set RST low");
+            self.rst.set_low().map_err(Error::from_digital)
+        }
+    }
+
     /// Send command over SPI bus
     ///
     /// # Errors
@@ -394,29 +814,81 @@ async fn send_data(&mut self, data: &[u8]) -> Result<(), Error> {
         self.write(data).await
     }
 
-    /// Write data to SPI bus
+    /// Send a byte repeated over SPI bus
+    ///
+    /// Data is sent from a small chunk, to avoid filling it all on the stack.
     ///
     /// # Errors
     ///
     /// Returns an error if writing to SPI bus fails.
-    async fn write(&mut self, data: &[u8]) -> Result<(), Error> {
-        if log_enabled!(Trace) {
-            trace!("Write {} bytes to SPI", data.len());
+    async fn send_filled_data(&mut self, byte: u8, length: usize) -> Result<(), Error> {
+        trace!("Fill data");
+        let buffer = [byte; ENCODING_CHUNK_SIZE];
+        for start in (0..length).step_by(ENCODING_CHUNK_SIZE) {
+            let size = (length - start).min(ENCODING_CHUNK_SIZE);
+            self.send_data(&buffer[..size]).await?;
         }
 
-        // Linux has a default limit of 4096 bytes per SPI transfer
-        // see https://raspberrypi.stackexchange.com/questions/65595/spi-transfer-fails-with-buffer-size-greater-than-4096
-        if cfg!(target_os = "linux") {
-            trace!("Write bytes in chunks of 4096 bytes");
-            for data_chunk in data.chunks(4096) {
-                self.spi.write(data_chunk).await?;
-            }
-        } else if self.individual_writes {
-            for datum in data {
-                self.spi.write(&[*datum]).await?;
+        Ok(())
+    }
+
+    /// Send encoded data over SPI bus
+    ///
+    /// Data is encoded in small chunks, to avoid copying it all on the stack.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to SPI bus fails.
+    async fn send_encoded_data(&mut self, data: &[u8], encoding: Encoding) -> Result<(), Error> {
+        trace!("Encode data");
+        let mut buffer = [0x00; ENCODING_CHUNK_SIZE];
+        for chunk in encoding.chunks(data, ENCODING_CHUNK_SIZE) {
+            let buffer = &mut buffer[..chunk.len()];
+            encoding.encode(chunk, buffer);
+            self.send_data(buffer).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Send run-length encoded data over SPI bus
+    ///
+    /// Data is decoded and encoded in small chunks, to avoid copying it all
+    /// on the stack.
+    /// Lines of supported panels are shorter than a chunk, so a line with
+    /// reversed bytes is always encoded at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to SPI bus fails.
+    async fn send_compressed_data(&mut self, data: &[u8], encoding: Encoding) -> Result<(), Error> {
+        trace!("Decode data");
+        let mut decoder = Decoder::new(data);
+        let mut chunk = [0x00; ENCODING_CHUNK_SIZE];
+        let mut buffer = [0x00; ENCODING_CHUNK_SIZE];
+        loop {
+            let length = decoder.read(&mut chunk);
+            if length == 0 {
+                break;
             }
-        } else {
-            self.spi.write(data).await?;
+            let buffer = &mut buffer[..length];
+            encoding.encode(&chunk[..length], buffer);
+            self.send_data(buffer).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Write data to SPI bus
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to SPI bus fails.
+    async fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        trace!("Write {} bytes to SPI", data.len());
+
+        for chunk in data.chunks(self.write_strategy.chunk_size(data.len())) {
+            self.spi.write(chunk).await?;
         }
 
         Ok(())
@@ -426,36 +898,544 @@ async fn write(&mut self, data: &[u8]) -> Result<(), Error> {
     ///
     /// # Errors
     ///
-    /// Returns an error if reading the busy pin fails.
+    /// Returns an error if reading the busy pin fails, or if the display is
+    /// still busy after the timeout.
     async fn wait_until_idle(&mut self) -> Result<(), Error> {
-        if IS_BUSY_LOW {
-            self.busy.wait_for_high().await.map_err(Error::from_digital)
+        let busy = &mut self.busy;
+        let wait = async {
+            if IS_BUSY_LOW {
+                busy.wait_for_high().await
+            } else {
+                busy.wait_for_low().await
+            }
+        };
+
+        let result = if let Some(timeout) = self.busy_timeout {
+            let timeout = u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX);
+            let timeout = self.delay.delay_ms(timeout);
+            with_timeout(wait, timeout)
+                .await
+                .ok_or(Error::BusyTimeout)?
+        } else {
+            wait.await
+        };
+
+        result.map_err(Error::from_digital)
+    }
+}
+
+/// A refresh of the display in progress
+///
+/// The display cannot be used until the refresh is complete.
+/// If the handle is dropped without waiting, the next operation on the
+/// display waits for the refresh to complete.
+#[must_use = "the refresh must be waited for before using the display again"]
+pub struct RefreshHandle<
+    'display,
+    SPI: SpiDevice,
+    BUSY: Wait,
+    RST: OutputPin,
+    DC: OutputPin,
+    DELAY: DelayNs,
+    PANEL: Panel = Epd1in54bV2,
+> {
+    /// Display being refreshed
+    display: &'display mut Display<SPI, BUSY, RST, DC, DELAY, PANEL>,
+}
+
+impl<SPI, BUSY, RST, DC, DELAY, PANEL> RefreshHandle<'_, SPI, BUSY, RST, DC, DELAY, PANEL>
+where
+    SPI: SpiDevice,
+    BUSY: Wait,
+    RST: OutputPin,
+    DC: OutputPin,
+    DELAY: DelayNs,
+    PANEL: Panel,
+{
+    /// Check whether the display is still refreshing
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading the busy pin fails.
+    pub fn is_busy(&mut self) -> Result<bool, Error>
+    where
+        BUSY: InputPin,
+    {
+        let busy = &mut self.display.busy;
+        let is_busy = if IS_BUSY_LOW {
+            busy.is_low()
         } else {
-            self.busy.wait_for_low().await.map_err(Error::from_digital)
+            busy.is_high()
+        };
+        is_busy.map_err(Error::from_digital)
+    }
+
+    /// Wait until the refresh is complete
+    ///
+    /// If the display is stuck and recovery is enabled, it is reset and
+    /// initialized again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any commands to the display fails
+    pub async fn wait(self) -> Result<(), Error> {
+        debug!("Wait for refresh");
+        self.display.wait_for_refresh().await?;
+        debug!("Wait for refresh / Done");
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "draw-target")]
+/// A display owning a buffer
+///
+/// The display can be drawn on directly, and [`Self::flush()`] updates it
+/// with the content of the buffer.
+/// With a frame borrowed through [`Self::with_frame()`], only the areas that
+/// changed since the last update are transferred, as with
+/// [`Display::draw_buffer_changes()`].
+pub struct BufferedDisplay<
+    'frame,
+    SPI: SpiDevice,
+    BUSY: Wait,
+    RST: OutputPin,
+    DC: OutputPin,
+    DELAY: DelayNs,
+    const WIDTH: usize,
+    const HEIGHT: usize,
+    const BYTE_SIZE: usize,
+    PANEL: Panel = Epd1in54bV2,
+> {
+    /// Display
+    display: Display<SPI, BUSY, RST, DC, DELAY, PANEL>,
+
+    /// Buffer drawn on
+    buffer: Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+
+    /// Last frame sent to the display, if tracked
+    frame: Option<&'frame mut Frame<PANEL>>,
+}
+
+#[cfg(feature = "draw-target")]
+impl<
+        'frame,
+        SPI,
+        BUSY,
+        RST,
+        DC,
+        DELAY,
+        const WIDTH: usize,
+        const HEIGHT: usize,
+        const BYTE_SIZE: usize,
+        PANEL,
+    > BufferedDisplay<'frame, SPI, BUSY, RST, DC, DELAY, WIDTH, HEIGHT, BYTE_SIZE, PANEL>
+where
+    SPI: SpiDevice,
+    BUSY: Wait,
+    RST: OutputPin,
+    DC: OutputPin,
+    DELAY: DelayNs,
+    PANEL: Panel,
+{
+    /// Create a new buffered display
+    ///
+    /// The buffer is not sent to the display until it is flushed.
+    #[must_use]
+    pub fn new(
+        display: Display<SPI, BUSY, RST, DC, DELAY, PANEL>,
+        buffer: Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+    ) -> Self {
+        Self {
+            display,
+            buffer,
+            frame: None,
+        }
+    }
+
+    /// Track the last frame sent to the display in a frame owned by the
+    /// caller
+    ///
+    /// Flushing then skips unchanged buffers, and transfers only the areas
+    /// that changed.
+    #[must_use]
+    pub fn with_frame(mut self, frame: &'frame mut Frame<PANEL>) -> Self {
+        self.frame = Some(frame);
+        self
+    }
+
+    /// Get the display
+    #[must_use]
+    pub fn display(&self) -> &Display<SPI, BUSY, RST, DC, DELAY, PANEL> {
+        &self.display
+    }
+
+    /// Get the display mutably, e.g. for waking it up or putting it to sleep
+    pub fn display_mut(&mut self) -> &mut Display<SPI, BUSY, RST, DC, DELAY, PANEL> {
+        &mut self.display
+    }
+
+    /// Get the buffer
+    #[must_use]
+    pub fn buffer(&self) -> &Buffer<WIDTH, HEIGHT, BYTE_SIZE> {
+        &self.buffer
+    }
+
+    /// Get the buffer mutably, e.g. for setting its rotation
+    pub fn buffer_mut(&mut self) -> &mut Buffer<WIDTH, HEIGHT, BYTE_SIZE> {
+        &mut self.buffer
+    }
+
+    /// Split into display and buffer
+    #[must_use]
+    pub fn into_parts(
+        self,
+    ) -> (
+        Display<SPI, BUSY, RST, DC, DELAY, PANEL>,
+        Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+    ) {
+        (self.display, self.buffer)
+    }
+
+    /// Check whether the display already shows the content of the buffer
+    ///
+    /// In that case [`Self::flush()`] does nothing, so the display does not
+    /// need to be woken up.
+    /// Without a frame this is never known, so it always returns `false`.
+    #[must_use]
+    pub fn is_up_to_date(&self, mode: RefreshMode) -> bool {
+        self.frame
+            .as_deref()
+            .is_some_and(|frame| self.display.is_up_to_date(&self.buffer, frame, mode))
+    }
+
+    /// Update the display with the content of the buffer
+    ///
+    /// With a frame, only the areas that changed since the last update are
+    /// transferred, and with [`RefreshMode::Partial`] the display is updated
+    /// without flashing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer does not match the panel, or if any
+    /// commands to the display fails
+    pub async fn flush(&mut self, mode: RefreshMode) -> Result<(), Error> {
+        if let Some(frame) = self.frame.as_deref_mut() {
+            self.display
+                .draw_buffer_changes(&self.buffer, frame, mode)
+                .await
+        } else {
+            self.display.draw_buffer(&self.buffer, mode).await
         }
     }
 
-    /// Reset the display
+    /// Update the display with the content of the buffer, without waiting
+    /// for the refresh to complete
+    ///
+    /// See [`Display::start_draw_buffer_changes()`].
+    /// Return `None` if the display already shows the buffer.
     ///
     /// # Errors
     ///
-    /// Returns an error if setting any pin fails.
-    async fn hardware_reset(&mut self) -> Result<(), Error> {
-        debug!("Hardware reset");
-        trace!("Set RST high");
-        self.rst.set_high().map_err(Error::from_digital)?;
-        self.delay.delay_ms(10).await;
+    /// Returns an error if the buffer does not match the panel, or if any
+    /// commands to the display fails
+    #[allow(clippy::type_complexity)]
+    pub async fn start_flush(
+        &mut self,
+        mode: RefreshMode,
+    ) -> Result<Option<RefreshHandle<'_, SPI, BUSY, RST, DC, DELAY, PANEL>>, Error> {
+        if let Some(frame) = self.frame.as_deref_mut() {
+            self.display
+                .start_draw_buffer_changes(&self.buffer, frame, mode)
+                .await
+        } else {
+            self.display
+                .start_draw_buffer(&self.buffer, mode)
+                .await
+                .map(Some)
+        }
+    }
+}
+
+#[cfg(feature = "draw-target")]
+impl<
+        SPI,
+        BUSY,
+        RST,
+        DC,
+        DELAY,
+        const WIDTH: usize,
+        const HEIGHT: usize,
+        const BYTE_SIZE: usize,
+        PANEL,
+    > DrawTarget for BufferedDisplay<'_, SPI, BUSY, RST, DC, DELAY, WIDTH, HEIGHT, BYTE_SIZE, PANEL>
+where
+    SPI: SpiDevice,
+    BUSY: Wait,
+    RST: OutputPin,
+    DC: OutputPin,
+    DELAY: DelayNs,
+    PANEL: Panel,
+{
+    type Error = Infallible;
 
-        trace!("Set RST low");
-        self.rst.set_low().map_err(Error::from_digital)?;
-        self.delay.delay_ms(10).await;
+    type Color = Color;
 
-        trace!("Set RST high");
-        self.rst.set_high().map_err(Error::from_digital)?;
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.buffer.draw_iter(pixels)
+    }
 
-        self.delay.delay_ms(200).await;
-        debug!("Hardware reset / done");
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.buffer.fill_contiguous(area, colors)
+    }
 
-        Ok(())
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.buffer.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.buffer.clear(color)
+    }
+}
+
+#[cfg(feature = "draw-target")]
+impl<
+        SPI,
+        BUSY,
+        RST,
+        DC,
+        DELAY,
+        const WIDTH: usize,
+        const HEIGHT: usize,
+        const BYTE_SIZE: usize,
+        PANEL,
+    > OriginDimensions
+    for BufferedDisplay<'_, SPI, BUSY, RST, DC, DELAY, WIDTH, HEIGHT, BYTE_SIZE, PANEL>
+where
+    SPI: SpiDevice,
+    BUSY: Wait,
+    RST: OutputPin,
+    DC: OutputPin,
+    DELAY: DelayNs,
+    PANEL: Panel,
+{
+    fn size(&self) -> Size {
+        self.buffer.size()
+    }
+}
+
+/// Run a future until it completes or until a timeout expires
+///
+/// Returns `None` if the timeout expired first.
+async fn with_timeout<F, T>(future: F, timeout: T) -> Option<F::Output>
+where
+    F: Future,
+    T: Future<Output = ()>,
+{
+    let mut future = pin!(future);
+    let mut timeout = pin!(timeout);
+
+    poll_fn(|context| {
+        if let Poll::Ready(output) = future.as_mut().poll(context) {
+            Poll::Ready(Some(output))
+        } else if timeout.as_mut().poll(context).is_ready() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::transcript::block_on;
+    use crate::transcript::clearing;
+    use crate::transcript::initialization;
+    use crate::transcript::releasing;
+    use crate::transcript::BusyPin;
+    use crate::transcript::DcPin;
+    use crate::transcript::RecordingDelay;
+    use crate::transcript::RecordingSpi;
+    use crate::transcript::ResetPin;
+    use crate::transcript::Transcript;
+
+    #[cfg(feature = "draw-target")]
+    use embedded_graphics_core::draw_target::DrawTarget;
+    #[cfg(feature = "draw-target")]
+    use embedded_graphics_core::geometry::Point;
+    #[cfg(feature = "draw-target")]
+    use embedded_graphics_core::Pixel;
+
+    #[cfg(feature = "draw-target")]
+    use crate::transcript::first_drawing;
+    #[cfg(feature = "draw-target")]
+    use crate::transcript::second_drawing;
+    #[cfg(feature = "draw-target")]
+    use crate::Color;
+    #[cfg(feature = "draw-target")]
+    use crate::Epd1in54Buffer;
+    #[cfg(feature = "draw-target")]
+    use crate::Frame;
+
+    /// A display on recording hardware
+    type RecordingDisplay = Display<RecordingSpi, BusyPin, ResetPin, DcPin, RecordingDelay>;
+
+    /// Create an initialized display recording to a transcript
+    fn initialized_display(transcript: &Transcript) -> RecordingDisplay {
+        let (spi, busy, rst, dc, delay) = transcript.hardware();
+        let mut display = Display::new(spi, busy, rst, dc, delay);
+        assert_eq!(block_on(display.initialize()), Ok(()));
+        display
+    }
+
+    #[test]
+    fn initialize_transcript() {
+        let transcript = Transcript::new();
+        initialized_display(&transcript);
+
+        assert_eq!(transcript.take(), initialization());
+    }
+
+    #[test]
+    fn clear_transcript() {
+        let transcript = Transcript::new();
+        let mut display = initialized_display(&transcript);
+        transcript.take();
+
+        assert_eq!(block_on(display.clear()), Ok(()));
+        assert_eq!(transcript.take(), clearing());
+    }
+
+    #[test]
+    fn clear_writes() {
+        // Five writes for commands and parameters, and the rest for filling
+        // two planes of 5000 bytes in chunks of 64 bytes
+        let cases = [
+            (WriteStrategy::Bulk, 5 + 2 * 79),
+            (WriteStrategy::Chunked(32), 5 + 2 * (78 * 2 + 1)),
+            (WriteStrategy::PerByte, 5 + 2 * 5000),
+        ];
+        for (write_strategy, writes) in cases {
+            let transcript = Transcript::new();
+            let mut display = initialized_display(&transcript).with_write_strategy(write_strategy);
+            transcript.take();
+            transcript.take_writes();
+
+            assert_eq!(block_on(display.clear()), Ok(()));
+            assert_eq!(transcript.take(), clearing());
+            assert_eq!(transcript.take_writes(), writes, "{write_strategy:?}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "draw-target")]
+    fn draw_buffer_transcript() {
+        let transcript = Transcript::new();
+        let mut display = initialized_display(&transcript);
+        transcript.take();
+
+        let mut frame = Frame::new();
+        let mut buffer = Epd1in54Buffer::new();
+        let Ok(()) = buffer.draw_iter([
+            Pixel(Point::new(0, 0), Color::Black),
+            Pixel(Point::new(199, 199), Color::Chromatic),
+        ]);
+        assert_eq!(
+            block_on(display.draw_buffer_changes(&buffer, &mut frame, RefreshMode::Full)),
+            Ok(())
+        );
+        assert_eq!(transcript.take(), first_drawing());
+
+        let Ok(()) = buffer.draw_iter([Pixel(Point::new(8, 1), Color::Black)]);
+        assert_eq!(
+            block_on(display.draw_buffer_changes(&buffer, &mut frame, RefreshMode::Full)),
+            Ok(())
+        );
+        assert_eq!(transcript.take(), second_drawing());
+
+        assert_eq!(
+            block_on(display.draw_buffer_changes(&buffer, &mut frame, RefreshMode::Full)),
+            Ok(())
+        );
+        assert_eq!(transcript.take(), []);
+
+        // Without the frame, whole planes are transferred
+        assert_eq!(
+            block_on(display.draw_buffer(&buffer, RefreshMode::Full)),
+            Ok(())
+        );
+        assert_ne!(transcript.take(), []);
+    }
+
+    #[test]
+    #[cfg(feature = "draw-target")]
+    fn start_draw_buffer_transcript() {
+        let transcript = Transcript::new();
+        let mut display = initialized_display(&transcript);
+        transcript.take();
+
+        let mut buffer = Epd1in54Buffer::new();
+        let Ok(()) = buffer.draw_iter([
+            Pixel(Point::new(0, 0), Color::Black),
+            Pixel(Point::new(199, 199), Color::Chromatic),
+        ]);
+        let mut frame = Frame::new();
+        let refresh =
+            block_on(display.start_draw_buffer_changes(&buffer, &mut frame, RefreshMode::Full));
+        let waited = match refresh {
+            Ok(Some(refresh)) => block_on(refresh.wait()).map(|()| true),
+            Ok(None) => Ok(false),
+            Err(error) => Err(error),
+        };
+        assert_eq!(waited, Ok(true));
+        assert_eq!(transcript.take(), first_drawing());
+
+        assert!(display.is_up_to_date(&buffer, &frame, RefreshMode::Full));
+    }
+
+    #[test]
+    #[cfg(feature = "draw-target")]
+    fn buffered_display_transcript() {
+        let transcript = Transcript::new();
+        let mut frame = Frame::new();
+        let mut display =
+            BufferedDisplay::new(initialized_display(&transcript), Epd1in54Buffer::new())
+                .with_frame(&mut frame);
+        transcript.take();
+
+        let Ok(()) = display.draw_iter([
+            Pixel(Point::new(0, 0), Color::Black),
+            Pixel(Point::new(199, 199), Color::Chromatic),
+        ]);
+        assert!(!display.is_up_to_date(RefreshMode::Full));
+        assert_eq!(block_on(display.flush(RefreshMode::Full)), Ok(()));
+        assert_eq!(transcript.take(), first_drawing());
+
+        let Ok(()) = display.draw_iter([Pixel(Point::new(8, 1), Color::Black)]);
+        assert_eq!(block_on(display.flush(RefreshMode::Full)), Ok(()));
+        assert_eq!(transcript.take(), second_drawing());
+
+        assert!(display.is_up_to_date(RefreshMode::Full));
+        assert_eq!(block_on(display.flush(RefreshMode::Full)), Ok(()));
+        assert_eq!(transcript.take(), []);
+    }
+
+    #[test]
+    fn release_transcript() {
+        let transcript = Transcript::new();
+        let display = initialized_display(&transcript);
+        transcript.take();
+
+        assert!(block_on(display.release()).is_ok());
+        assert_eq!(transcript.take(), releasing());
     }
 }
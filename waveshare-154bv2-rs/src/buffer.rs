@@ -9,29 +9,23 @@
 //! Underlying graphical buffer
 
 use core::convert::Infallible;
+use core::ops::RangeInclusive;
 
 use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::Dimensions;
 use embedded_graphics_core::geometry::OriginDimensions;
 use embedded_graphics_core::geometry::Point;
 use embedded_graphics_core::geometry::Size;
+use embedded_graphics_core::image::GetPixel;
+use embedded_graphics_core::primitives::PointsIter;
+use embedded_graphics_core::primitives::Rectangle;
 use embedded_graphics_core::Pixel;
 
 use crate::Color;
 
-// /// Screen width
-// const WIDTH: usize = 8;
-
-// /// Screen height
-// const HEIGHT: usize = 8;
-
-// /// Screen size in bits
-// const BIT_SIZE: usize = WIDTH * HEIGHT;
-
-// /// Screen size in bits
-// const BYTE_SIZE: usize = BIT_SIZE / 8;
-
 /// A screen rotation
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Rotation {
     /// No rotation
     Rotate0,
@@ -49,7 +43,9 @@ pub enum Rotation {
 /// A buffer to draw tri-colors graphics
 ///
 /// `WIDTH` and `HEIGHT` are the screen width and height in pixels, while
-/// `BYTE_SIZE` is the screen size in bytes (width × height ÷ 8).
+/// `BYTE_SIZE` is the screen size in bytes (⌈width ÷ 8⌉ × height).
+/// Each row starts on a new byte, so when the width is not a multiple of 8
+/// the last bits of every row are unused.
 #[derive(Debug)]
 pub struct Buffer<const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize> {
     /// Buffer rotation
@@ -65,6 +61,9 @@ pub struct Buffer<const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usiz
 impl<const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize>
     Buffer<WIDTH, HEIGHT, BYTE_SIZE>
 {
+    /// Size of a row in bytes
+    const LINE_WIDTH: usize = WIDTH.div_ceil(8);
+
     /// Create a new graphical buffer
     #[must_use]
     pub const fn new() -> Self {
@@ -91,6 +90,85 @@ pub fn chromatic_buffer(&self) -> &[u8] {
     pub fn set_rotation(&mut self, rotation: Rotation) {
         self.rotation = rotation;
     }
+
+    /// Get screen rotation
+    #[must_use]
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// Get the color of a pixel
+    ///
+    /// The point is expressed in rotated coordinates, and `None` is returned
+    /// if it lies outside of the buffer.
+    #[must_use]
+    pub fn pixel(&self, point: Point) -> Option<Color> {
+        if !self.bounding_box().contains(point) {
+            return None;
+        }
+
+        let Point { x, y } = self.to_physical(point);
+        let (index, offset) = get_index_and_offset(Self::LINE_WIDTH, x, y);
+        let mask: u8 = 0b1000_0000 >> offset;
+        let black = self.black.get(index)? & mask == 0;
+        let chromatic = self.chromatic.get(index)? & mask == 0;
+
+        let color = if chromatic {
+            Color::Chromatic
+        } else if black {
+            Color::Black
+        } else {
+            Color::White
+        };
+        Some(color)
+    }
+
+    /// Convert an area from rotated coordinates to physical coordinates
+    ///
+    /// The area is clipped to the buffer, and the result is empty if the area
+    /// lies entirely outside of it.
+    #[must_use]
+    pub fn physical_area(&self, area: &Rectangle) -> Rectangle {
+        let area = area.intersection(&self.bounding_box());
+        match area.bottom_right() {
+            Some(bottom_right) => Rectangle::with_corners(
+                self.to_physical(area.top_left),
+                self.to_physical(bottom_right),
+            ),
+            None => Rectangle::zero(),
+        }
+    }
+
+    /// Convert a point from rotated coordinates to physical coordinates
+    fn to_physical(&self, point: Point) -> Point {
+        physical_point::<WIDTH, HEIGHT>(self.rotation, point)
+    }
+
+    /// Fill an area in physical coordinates with a color
+    ///
+    /// The area must lie within the buffer.
+    #[allow(clippy::cast_sign_loss)]
+    fn fill_physical(&mut self, area: &Rectangle, color: Color) {
+        let Some(bottom_right) = area.bottom_right() else {
+            return;
+        };
+        let columns = area.top_left.x as usize..=bottom_right.x as usize;
+        let rows = area.top_left.y as usize..=bottom_right.y as usize;
+
+        let (black, chromatic) = plane_bits(color);
+        if let Some(value) = black {
+            fill_plane(
+                &mut self.black,
+                Self::LINE_WIDTH,
+                columns.clone(),
+                rows.clone(),
+                value,
+            );
+        }
+        if let Some(value) = chromatic {
+            fill_plane(&mut self.chromatic, Self::LINE_WIDTH, columns, rows, value);
+        }
+    }
 }
 
 impl<const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize> Default
@@ -113,70 +191,617 @@ fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
+        let Size { width, height } = self.size();
+
         #[allow(clippy::pattern_type_mismatch)]
         let pixels = pixels.into_iter().filter(|Pixel(Point { x, y }, _color)| {
-            *x >= 0_i32 && *x < WIDTH as i32 && *y >= 0_i32 && *y < HEIGHT as i32
+            *x >= 0_i32 && *x < width as i32 && *y >= 0_i32 && *y < height as i32
         });
 
-        for Pixel(Point { x, y }, color) in pixels {
-            let (index, offset) = get_index_and_offset::<WIDTH>(x, y);
+        for Pixel(point, color) in pixels {
+            let Point { x, y } = self.to_physical(point);
+            let (index, offset) = get_index_and_offset(Self::LINE_WIDTH, x, y);
             if index >= BYTE_SIZE || offset >= 8 {
                 continue;
             }
-            let offset = 8 - offset - 1;
-            let mask: u8 = 0b0000_0001 << offset;
-            let reverse_mask: u8 = !mask;
-            match color {
-                Color::Black => {
-                    let original = self.black[index];
-                    self.black[index] = (original & reverse_mask) | (0 << offset);
-                }
-                Color::Chromatic => {
-                    let original = self.chromatic[index];
-                    self.chromatic[index] = original & reverse_mask | (0 << offset);
-                }
-                Color::White => {
-                    let original = self.black[index];
-                    self.black[index] = original & reverse_mask | (1 << offset);
-                    let original = self.chromatic[index];
-                    self.chromatic[index] = (original & reverse_mask) | (1 << offset);
-                }
-                Color::Transparent => {}
+            let mask: u8 = 0b1000_0000 >> offset;
+            let (black, chromatic) = plane_bits(color);
+            if let Some(value) = black {
+                set_bits(&mut self.black[index], mask, value);
+            }
+            if let Some(value) = chromatic {
+                set_bits(&mut self.chromatic[index], mask, value);
             }
         }
 
         Ok(())
     }
+
+    /// Fill a contiguous area with colors
+    ///
+    /// Without rotation, pixels are collected in whole bytes before being
+    /// written to the planes.
+    /// Otherwise consecutive pixels do not share bytes, and they are drawn
+    /// individually.
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        if self.rotation != Rotation::Rotate0 {
+            return self.draw_iter(
+                area.points()
+                    .zip(colors)
+                    .map(|(point, color)| Pixel(point, color)),
+            );
+        }
+
+        let clipped = area.intersection(&self.bounding_box());
+        let mut patch = BytePatch::default();
+        for (point, color) in area.points().zip(colors) {
+            if !clipped.contains(point) {
+                continue;
+            }
+
+            let (index, offset) = get_index_and_offset(Self::LINE_WIDTH, point.x, point.y);
+            if index != patch.index {
+                patch.apply(&mut self.black, &mut self.chromatic);
+                patch = BytePatch {
+                    index,
+                    ..BytePatch::default()
+                };
+            }
+            patch.add(0b1000_0000 >> offset, color);
+        }
+        patch.apply(&mut self.black, &mut self.chromatic);
+
+        Ok(())
+    }
+
+    /// Fill an area with a single color
+    ///
+    /// Whole bytes are written at once, and only the bytes at the left and
+    /// right edges are masked.
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = self.physical_area(area);
+        self.fill_physical(&area, color);
+
+        Ok(())
+    }
+
+    /// Fill the whole buffer with a single color
+    #[allow(clippy::cast_possible_truncation)]
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let area = Rectangle::new(Point::zero(), Size::new(WIDTH as u32, HEIGHT as u32));
+        self.fill_physical(&area, color);
+
+        Ok(())
+    }
 }
 
-/// Get index and offset
-fn get_index_and_offset<const WIDTH: usize>(x: i32, y: i32) -> (usize, usize) {
-    let bit_index = get_bit_index::<WIDTH>(x, y);
-    get_index_and_offset_from_bit_index(bit_index)
+impl<const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize> GetPixel
+    for Buffer<WIDTH, HEIGHT, BYTE_SIZE>
+{
+    type Color = Color;
+
+    fn pixel(&self, point: Point) -> Option<Self::Color> {
+        Buffer::pixel(self, point)
+    }
 }
 
-/// Get bit index
-#[allow(clippy::cast_sign_loss)]
-fn get_bit_index<const WIDTH: usize>(x: i32, y: i32) -> usize {
-    x as usize + y as usize * WIDTH
+/// Convert a point from rotated coordinates to physical coordinates
+///
+/// `WIDTH` and `HEIGHT` are the physical screen width and height in pixels.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+pub(crate) fn physical_point<const WIDTH: usize, const HEIGHT: usize>(
+    rotation: Rotation,
+    point: Point,
+) -> Point {
+    let width = WIDTH as i32;
+    let height = HEIGHT as i32;
+    let Point { x, y } = point;
+    match rotation {
+        Rotation::Rotate0 => Point::new(x, y),
+        Rotation::Rotate90 => Point::new(width - 1 - y, x),
+        Rotation::Rotate180 => Point::new(width - 1 - x, height - 1 - y),
+        Rotation::Rotate270 => Point::new(y, height - 1 - x),
+    }
 }
 
-/// Get index and offset from bit index
-fn get_index_and_offset_from_bit_index(bit_index: usize) -> (usize, usize) {
-    let index = bit_index >> 3_i32;
-    let offset = bit_index & 0b0000_0111;
+/// Get the screen size in rotated coordinates
+///
+/// `WIDTH` and `HEIGHT` are the physical screen width and height in pixels.
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn rotated_size<const WIDTH: usize, const HEIGHT: usize>(rotation: Rotation) -> Size {
+    match rotation {
+        Rotation::Rotate0 | Rotation::Rotate180 => Size::new(WIDTH as u32, HEIGHT as u32),
+        Rotation::Rotate90 | Rotation::Rotate270 => Size::new(HEIGHT as u32, WIDTH as u32),
+    }
+}
+
+/// Values of the bits of each plane for a color
+///
+/// The first value is for the black plane and the second for the chromatic
+/// plane, `None` means that the plane is left unchanged.
+/// Every color except transparent sets both planes, so that at most one plane
+/// is active for each pixel.
+pub(crate) fn plane_bits(color: Color) -> (Option<bool>, Option<bool>) {
+    match color {
+        Color::Black => (Some(false), Some(true)),
+        Color::Chromatic => (Some(true), Some(false)),
+        Color::White => (Some(true), Some(true)),
+        Color::Transparent => (None, None),
+    }
+}
+
+/// Set or clear the bits of a byte selected by a mask
+pub(crate) fn set_bits(byte: &mut u8, mask: u8, value: bool) {
+    if value {
+        *byte |= mask;
+    } else {
+        *byte &= !mask;
+    }
+}
+
+/// Fill a rectangular area of a plane
+///
+/// Columns and rows are expressed in pixels.
+/// Bytes in the middle of each row are written at once, and only the bytes at
+/// the edges are masked.
+/// Bytes past the end of the plane are skipped, as in the per-pixel path.
+pub(crate) fn fill_plane(
+    plane: &mut [u8],
+    line_width: usize,
+    columns: RangeInclusive<usize>,
+    rows: RangeInclusive<usize>,
+    value: bool,
+) {
+    let first_byte = columns.start() / 8;
+    let last_byte = columns.end() / 8;
+    let first_mask = u8::MAX >> (columns.start() % 8);
+    let last_mask = u8::MAX << (7 - columns.end() % 8);
+    let filler = if value { u8::MAX } else { 0x00 };
+
+    for row in rows {
+        let start = row * line_width;
+        let end = plane.len().min(start + line_width);
+        let Some(line) = plane.get_mut(start..end) else {
+            // Rows are increasing, so all following rows are outside too
+            break;
+        };
+        if first_byte == last_byte {
+            if let Some(byte) = line.get_mut(first_byte) {
+                set_bits(byte, first_mask & last_mask, value);
+            }
+        } else {
+            if let Some(byte) = line.get_mut(first_byte) {
+                set_bits(byte, first_mask, value);
+            }
+            if let Some(middle) = line.get_mut(first_byte + 1..last_byte.min(end - start)) {
+                middle.fill(filler);
+            }
+            if let Some(byte) = line.get_mut(last_byte) {
+                set_bits(byte, last_mask, value);
+            }
+        }
+    }
+}
+
+/// Pending changes to a byte of both planes
+#[derive(Default)]
+struct BytePatch {
+    /// Index of the byte
+    index: usize,
+
+    /// Bits of the black plane to set
+    black_set: u8,
+
+    /// Bits of the black plane to clear
+    black_clear: u8,
+
+    /// Bits of the chromatic plane to set
+    chromatic_set: u8,
+
+    /// Bits of the chromatic plane to clear
+    chromatic_clear: u8,
+}
+
+impl BytePatch {
+    /// Add a pixel selected by a mask
+    fn add(&mut self, mask: u8, color: Color) {
+        let (black, chromatic) = plane_bits(color);
+        match black {
+            Some(true) => self.black_set |= mask,
+            Some(false) => self.black_clear |= mask,
+            None => {}
+        }
+        match chromatic {
+            Some(true) => self.chromatic_set |= mask,
+            Some(false) => self.chromatic_clear |= mask,
+            None => {}
+        }
+    }
+
+    /// Apply changes to the planes
+    fn apply(&self, black: &mut [u8], chromatic: &mut [u8]) {
+        if let Some(byte) = black.get_mut(self.index) {
+            *byte = (*byte & !self.black_clear) | self.black_set;
+        }
+        if let Some(byte) = chromatic.get_mut(self.index) {
+            *byte = (*byte & !self.chromatic_clear) | self.chromatic_set;
+        }
+    }
+}
+
+/// Get index and offset
+///
+/// Rows are `line_width` bytes long, and the offset is the bit index within
+/// the byte, starting from the most significant bit.
+#[allow(clippy::cast_sign_loss)]
+pub(crate) fn get_index_and_offset(line_width: usize, x: i32, y: i32) -> (usize, usize) {
+    let x = x as usize;
+    let y = y as usize;
+    let index = y * line_width + (x >> 3_i32);
+    let offset = x & 0b0000_0111;
     (index, offset)
 }
 
 impl<const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize> OriginDimensions
     for Buffer<WIDTH, HEIGHT, BYTE_SIZE>
 {
-    #[allow(clippy::cast_possible_truncation)]
     fn size(&self) -> Size {
-        Size::new(WIDTH as u32, HEIGHT as u32)
+        rotated_size::<WIDTH, HEIGHT>(self.rotation)
     }
 }
 
 /// A buffer sized for 1.54 in displays
 #[allow(clippy::module_name_repetitions)]
 pub type Epd1in54Buffer = Buffer<200, 200, 5000>;
+
+/// A buffer sized for 2.13 in displays
+#[allow(clippy::module_name_repetitions)]
+pub type Epd2in13Buffer = Buffer<122, 250, 4000>;
+
+/// A buffer sized for 2.9 in displays
+#[allow(clippy::module_name_repetitions)]
+pub type Epd2in9Buffer = Buffer<128, 296, 4736>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small non-square buffer, so that swapped axes are detected
+    type TestBuffer = Buffer<16, 8, 16>;
+
+    /// Draw a black and a chromatic pixel on a rotated buffer
+    fn draw_with_rotation(rotation: Rotation) -> TestBuffer {
+        let mut buffer = TestBuffer::new();
+        buffer.set_rotation(rotation);
+
+        let pixels = [
+            Pixel(Point::new(1, 2), Color::Black),
+            Pixel(Point::new(0, 0), Color::Chromatic),
+        ];
+        let Ok(()) = buffer.draw_iter(pixels);
+
+        buffer
+    }
+
+    /// Build an expected plane with a single cleared bit
+    fn plane_with_cleared_bit(index: usize, mask: u8) -> [u8; 16] {
+        let mut plane = [0xff; 16];
+        plane[index] = !mask;
+        plane
+    }
+
+    #[test]
+    fn rotate_0() {
+        let buffer = draw_with_rotation(Rotation::Rotate0);
+
+        assert_eq!(buffer.size(), Size::new(16, 8));
+        assert_eq!(
+            buffer.black_buffer(),
+            plane_with_cleared_bit(4, 0b0100_0000)
+        );
+        assert_eq!(
+            buffer.chromatic_buffer(),
+            plane_with_cleared_bit(0, 0b1000_0000)
+        );
+    }
+
+    #[test]
+    fn rotate_90() {
+        let buffer = draw_with_rotation(Rotation::Rotate90);
+
+        assert_eq!(buffer.size(), Size::new(8, 16));
+        assert_eq!(
+            buffer.black_buffer(),
+            plane_with_cleared_bit(3, 0b0000_0100)
+        );
+        assert_eq!(
+            buffer.chromatic_buffer(),
+            plane_with_cleared_bit(1, 0b0000_0001)
+        );
+    }
+
+    #[test]
+    fn rotate_180() {
+        let buffer = draw_with_rotation(Rotation::Rotate180);
+
+        assert_eq!(buffer.size(), Size::new(16, 8));
+        assert_eq!(
+            buffer.black_buffer(),
+            plane_with_cleared_bit(11, 0b0000_0010)
+        );
+        assert_eq!(
+            buffer.chromatic_buffer(),
+            plane_with_cleared_bit(15, 0b0000_0001)
+        );
+    }
+
+    #[test]
+    fn rotate_270() {
+        let buffer = draw_with_rotation(Rotation::Rotate270);
+
+        assert_eq!(buffer.size(), Size::new(8, 16));
+        assert_eq!(
+            buffer.black_buffer(),
+            plane_with_cleared_bit(12, 0b0010_0000)
+        );
+        assert_eq!(
+            buffer.chromatic_buffer(),
+            plane_with_cleared_bit(14, 0b1000_0000)
+        );
+    }
+
+    #[test]
+    fn physical_area_rotate_90() {
+        let mut buffer = TestBuffer::new();
+        buffer.set_rotation(Rotation::Rotate90);
+
+        let area = Rectangle::new(Point::new(1, 2), Size::new(3, 20));
+
+        assert_eq!(
+            buffer.physical_area(&area),
+            Rectangle::with_corners(Point::new(0, 1), Point::new(13, 3))
+        );
+    }
+
+    #[test]
+    fn row_stride_with_partial_bytes() {
+        let mut buffer = Buffer::<12, 2, 4>::new();
+
+        let pixels = [
+            Pixel(Point::new(11, 0), Color::Black),
+            Pixel(Point::new(0, 1), Color::Black),
+        ];
+        let Ok(()) = buffer.draw_iter(pixels);
+
+        assert_eq!(
+            buffer.black_buffer(),
+            [0xff, 0b1110_1111, 0b0111_1111, 0xff]
+        );
+    }
+
+    /// A buffer with a width that is not a multiple of 8
+    type OddBuffer = Buffer<21, 10, 30>;
+
+    /// All rotations
+    const ROTATIONS: [Rotation; 4] = [
+        Rotation::Rotate0,
+        Rotation::Rotate90,
+        Rotation::Rotate180,
+        Rotation::Rotate270,
+    ];
+
+    /// All colors
+    const COLORS: [Color; 4] = [
+        Color::Black,
+        Color::Chromatic,
+        Color::White,
+        Color::Transparent,
+    ];
+
+    /// Areas covering partial bytes, whole bytes, and the buffer edges
+    fn areas() -> [Rectangle; 6] {
+        [
+            Rectangle::new(Point::new(0, 0), Size::new(21, 10)),
+            Rectangle::new(Point::new(3, 1), Size::new(4, 3)),
+            Rectangle::new(Point::new(2, 2), Size::new(17, 5)),
+            Rectangle::new(Point::new(8, 0), Size::new(8, 10)),
+            Rectangle::new(Point::new(-5, -3), Size::new(40, 8)),
+            Rectangle::new(Point::new(30, 30), Size::new(2, 2)),
+        ]
+    }
+
+    /// Create a buffer with a pattern of all colors
+    fn patterned_buffer(rotation: Rotation) -> OddBuffer {
+        let mut buffer = OddBuffer::new();
+        buffer.set_rotation(rotation);
+
+        let pixels = buffer
+            .bounding_box()
+            .points()
+            .zip(COLORS.iter().copied().cycle().skip(1))
+            .map(|(point, color)| Pixel(point, color));
+        let Ok(()) = buffer.draw_iter(pixels);
+
+        buffer
+    }
+
+    /// Fill an area through the per-pixel path
+    fn fill_per_pixel(
+        buffer: &mut OddBuffer,
+        area: &Rectangle,
+        colors: impl Iterator<Item = Color>,
+    ) {
+        let pixels = area
+            .points()
+            .zip(colors)
+            .map(|(point, color)| Pixel(point, color));
+        let Ok(()) = buffer.draw_iter(pixels);
+    }
+
+    /// Check that two buffers have the same planes
+    fn assert_same_planes(actual: &OddBuffer, expected: &OddBuffer) {
+        assert_eq!(actual.black_buffer(), expected.black_buffer());
+        assert_eq!(actual.chromatic_buffer(), expected.chromatic_buffer());
+    }
+
+    #[test]
+    fn fill_solid_matches_per_pixel() {
+        for rotation in ROTATIONS {
+            for color in COLORS {
+                for area in areas() {
+                    let mut actual = patterned_buffer(rotation);
+                    let Ok(()) = actual.fill_solid(&area, color);
+
+                    let mut expected = patterned_buffer(rotation);
+                    fill_per_pixel(&mut expected, &area, core::iter::repeat(color));
+
+                    assert_same_planes(&actual, &expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fill_contiguous_matches_per_pixel() {
+        for rotation in ROTATIONS {
+            for area in areas() {
+                let colors = || COLORS.iter().copied().cycle().skip(2);
+
+                let mut actual = patterned_buffer(rotation);
+                let Ok(()) = actual.fill_contiguous(&area, colors());
+
+                let mut expected = patterned_buffer(rotation);
+                fill_per_pixel(&mut expected, &area, colors());
+
+                assert_same_planes(&actual, &expected);
+            }
+        }
+    }
+
+    #[test]
+    fn fill_contiguous_with_few_colors() {
+        let area = Rectangle::new(Point::new(1, 1), Size::new(10, 5));
+        let colors = [Color::Black; 13];
+
+        let mut actual = patterned_buffer(Rotation::Rotate0);
+        let Ok(()) = actual.fill_contiguous(&area, colors);
+
+        let mut expected = patterned_buffer(Rotation::Rotate0);
+        fill_per_pixel(&mut expected, &area, colors.into_iter());
+
+        assert_same_planes(&actual, &expected);
+    }
+
+    #[test]
+    fn clear_matches_per_pixel() {
+        for rotation in ROTATIONS {
+            for color in COLORS {
+                let mut actual = patterned_buffer(rotation);
+                let Ok(()) = actual.clear(color);
+
+                let mut expected = patterned_buffer(rotation);
+                let area = expected.bounding_box();
+                fill_per_pixel(&mut expected, &area, core::iter::repeat(color));
+
+                assert_same_planes(&actual, &expected);
+            }
+        }
+    }
+
+    /// A buffer whose byte size is too small for its dimensions
+    type UndersizedBuffer = Buffer<21, 10, 25>;
+
+    #[test]
+    fn undersized_buffer_matches_per_pixel() {
+        for rotation in ROTATIONS {
+            for area in areas() {
+                let mut actual = UndersizedBuffer::new();
+                actual.set_rotation(rotation);
+                let Ok(()) = actual.fill_solid(&area, Color::Black);
+
+                let mut expected = UndersizedBuffer::new();
+                expected.set_rotation(rotation);
+                let pixels = area.points().map(|point| Pixel(point, Color::Black));
+                let Ok(()) = expected.draw_iter(pixels);
+
+                assert_eq!(actual.black_buffer(), expected.black_buffer());
+                assert_eq!(actual.chromatic_buffer(), expected.chromatic_buffer());
+            }
+
+            let mut actual = UndersizedBuffer::new();
+            actual.set_rotation(rotation);
+            let Ok(()) = actual.clear(Color::Chromatic);
+
+            let mut expected = UndersizedBuffer::new();
+            expected.set_rotation(rotation);
+            let pixels = expected
+                .bounding_box()
+                .points()
+                .map(|point| Pixel(point, Color::Chromatic));
+            let Ok(()) = expected.draw_iter(pixels);
+
+            assert_eq!(actual.black_buffer(), expected.black_buffer());
+            assert_eq!(actual.chromatic_buffer(), expected.chromatic_buffer());
+        }
+    }
+
+    #[test]
+    fn colors_are_exclusive() {
+        let mut buffer = TestBuffer::new();
+
+        let pixels = [
+            Pixel(Point::new(0, 0), Color::Chromatic),
+            Pixel(Point::new(0, 0), Color::Black),
+            Pixel(Point::new(1, 0), Color::Black),
+            Pixel(Point::new(1, 0), Color::Chromatic),
+        ];
+        let Ok(()) = buffer.draw_iter(pixels);
+
+        assert_eq!(buffer.black_buffer()[0], 0b0111_1111);
+        assert_eq!(buffer.chromatic_buffer()[0], 0b1011_1111);
+    }
+
+    #[test]
+    fn pixel_readback() {
+        for rotation in ROTATIONS {
+            let mut buffer = TestBuffer::new();
+            buffer.set_rotation(rotation);
+
+            let pixels = [
+                Pixel(Point::new(1, 2), Color::Black),
+                Pixel(Point::new(3, 4), Color::Chromatic),
+                Pixel(Point::new(5, 6), Color::Black),
+                Pixel(Point::new(5, 6), Color::Transparent),
+            ];
+            let Ok(()) = buffer.draw_iter(pixels);
+
+            assert_eq!(buffer.pixel(Point::new(1, 2)), Some(Color::Black));
+            assert_eq!(buffer.pixel(Point::new(3, 4)), Some(Color::Chromatic));
+            assert_eq!(buffer.pixel(Point::new(5, 6)), Some(Color::Black));
+            assert_eq!(buffer.pixel(Point::new(0, 0)), Some(Color::White));
+            assert_eq!(buffer.pixel(Point::new(-1, 0)), None);
+        }
+
+        let buffer = TestBuffer::new();
+        assert_eq!(buffer.pixel(Point::new(16, 0)), None);
+        assert_eq!(buffer.pixel(Point::new(0, 8)), None);
+    }
+
+    #[test]
+    fn rotated_bounds() {
+        let mut buffer = TestBuffer::new();
+        buffer.set_rotation(Rotation::Rotate90);
+
+        let pixels = [
+            Pixel(Point::new(8, 0), Color::Black),
+            Pixel(Point::new(0, 16), Color::Black),
+            Pixel(Point::new(-1, 0), Color::Black),
+        ];
+        let Ok(()) = buffer.draw_iter(pixels);
+
+        assert_eq!(buffer.black_buffer(), [0xff; 16]);
+    }
+}
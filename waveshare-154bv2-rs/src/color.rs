@@ -12,10 +12,12 @@
 use embedded_graphics_core::pixelcolor::BinaryColor;
 use embedded_graphics_core::pixelcolor::PixelColor;
 use embedded_graphics_core::pixelcolor::Rgb888;
+use embedded_graphics_core::pixelcolor::RgbColor;
 use embedded_graphics_core::prelude::RawData;
 
 /// A tri-color
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Color {
     /// Black
     Black,
@@ -57,9 +59,13 @@ fn from(color: BinaryColor) -> Self {
 }
 
 impl From<Rgb888> for Color {
+    /// Convert to the closest color of the palette
+    ///
+    /// Reddish colors are converted to [`Color::Chromatic`], while other
+    /// colors are converted to black or white.
     fn from(color: Rgb888) -> Self {
-        let binary_color: BinaryColor = color.into();
-        binary_color.into()
+        let (color, _) = closest_color([color.r(), color.g(), color.b()].map(i32::from));
+        color
     }
 }
 
@@ -73,3 +79,32 @@ fn from(color: Color) -> Self {
         }
     }
 }
+
+/// Displayable colors with their RGB components
+const PALETTE: [(Color, [i32; 3]); 3] = [
+    (Color::Black, [0, 0, 0]),
+    (Color::White, [255, 255, 255]),
+    (Color::Chromatic, [255, 0, 0]),
+];
+
+/// Find the displayable color closest to RGB components
+///
+/// Components can lie outside of the range 0-255, e.g. when accumulating
+/// dithering errors.
+/// Return the color together with its RGB components.
+pub(crate) fn closest_color(rgb: [i32; 3]) -> (Color, [i32; 3]) {
+    let [red, green, blue] = rgb;
+    let mut closest = PALETTE[0];
+    let mut closest_distance = i32::MAX;
+    for (color, components) in PALETTE {
+        let [palette_red, palette_green, palette_blue] = components;
+        let distance = (red - palette_red).pow(2)
+            + (green - palette_green).pow(2)
+            + (blue - palette_blue).pow(2);
+        if distance < closest_distance {
+            closest = (color, components);
+            closest_distance = distance;
+        }
+    }
+    closest
+}
@@ -20,9 +20,18 @@
 /// Command for software reset
 pub const SOFTWARE_RESET: u8 = 0x12;
 
+/// Command for temperature sensor control
+pub const TEMPERATURE_SENSOR_CONTROL: u8 = 0x18;
+
+/// Command for writing to temperature register
+pub const WRITE_TEMPERATURE_REGISTER: u8 = 0x1a;
+
 /// Command for master activation
 pub const MASTER_ACTIVATION: u8 = 0x20;
 
+/// Command for display update control 1
+pub const DISPLAY_UPDATE_CONTROL_1: u8 = 0x21;
+
 /// Command for display update control 2
 pub const DISPLAY_UPDATE_CONTROL_2: u8 = 0x22;
 
@@ -32,6 +41,9 @@
 /// Command for write RAM chromatic
 pub const WRITE_RAM_CHROMATIC: u8 = 0x26;
 
+/// Command for writing LUT register
+pub const WRITE_LUT_REGISTER: u8 = 0x32;
+
 /// Command for border waveform control
 pub const BORDER_WAVEFORM_CONTROL: u8 = 0x3c;
 
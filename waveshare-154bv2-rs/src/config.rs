@@ -0,0 +1,465 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Configuration of the display controller
+//!
+//! The configuration is applied when initializing the display.
+//! Settings left unspecified use the defaults of the panel.
+
+use crate::Panel;
+
+/// Mask of the scan direction bit in the driver output control command
+const GATE_SCAN_DIRECTION_MASK: u8 = 0b0000_0001;
+
+/// Mask of the X address direction bit in the data entry mode command
+const X_INCREMENT_MASK: u8 = 0b0000_0001;
+
+/// Mask of the Y address direction bit in the data entry mode command
+const Y_INCREMENT_MASK: u8 = 0b0000_0010;
+
+/// Waveform of the border around the active area
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Border {
+    /// Follow the waveform of black pixels
+    Black,
+
+    /// Follow the waveform of white pixels
+    White,
+
+    /// Follow the waveform of chromatic pixels
+    ///
+    /// On black and white panels the border stays white.
+    Chromatic,
+
+    /// Follow the common electrode voltage
+    Vcom,
+
+    /// Leave the border floating at high impedance
+    ///
+    /// The border keeps its current color.
+    HighImpedance,
+}
+
+impl Border {
+    /// Get the data for the border waveform control command
+    const fn register(self) -> u8 {
+        match self {
+            Self::Black => 0x04,
+            Self::White => 0x05,
+            Self::Chromatic => 0x06,
+            Self::Vcom => 0x80,
+            Self::HighImpedance => 0xc0,
+        }
+    }
+}
+
+/// Order in which the gate driver scans panel rows
+///
+/// Reversing the scan direction mirrors the image vertically.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GateScanDirection {
+    /// Scan from the first gate to the last one
+    Forward,
+
+    /// Scan from the last gate to the first one
+    Backward,
+}
+
+/// Mirroring of the image on the panel
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Mirroring {
+    /// Show the image as it is
+    #[default]
+    None,
+
+    /// Swap left and right
+    ///
+    /// On panels whose width is not a multiple of 8 pixels, the image is
+    /// shifted by the unused bits at the end of each line.
+    Horizontal,
+
+    /// Swap top and bottom
+    Vertical,
+
+    /// Swap both left and right, and top and bottom
+    Both,
+}
+
+impl Mirroring {
+    /// Check whether left and right are swapped
+    const fn is_horizontal(self) -> bool {
+        matches!(self, Self::Horizontal | Self::Both)
+    }
+
+    /// Check whether top and bottom are swapped
+    const fn is_vertical(self) -> bool {
+        matches!(self, Self::Vertical | Self::Both)
+    }
+}
+
+/// Direction of RAM address counters after each written byte
+///
+/// The counters always move along X first.
+/// The driver orders data according to the mode, so that the image does not
+/// change.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DataEntryMode {
+    /// Decrement both X and Y
+    DecrementXDecrementY,
+
+    /// Increment X and decrement Y
+    IncrementXDecrementY,
+
+    /// Decrement X and increment Y
+    DecrementXIncrementY,
+
+    /// Increment both X and Y
+    IncrementXIncrementY,
+}
+
+impl DataEntryMode {
+    /// Get the data for the data entry mode command
+    const fn register(self) -> u8 {
+        match self {
+            Self::DecrementXDecrementY => 0x00,
+            Self::IncrementXDecrementY => 0x01,
+            Self::DecrementXIncrementY => 0x02,
+            Self::IncrementXIncrementY => 0x03,
+        }
+    }
+}
+
+/// Configuration of the display controller
+///
+/// ```
+/// use waveshare_154bv2_rs::Border;
+/// use waveshare_154bv2_rs::DisplayConfig;
+/// use waveshare_154bv2_rs::Mirroring;
+///
+/// let config = DisplayConfig::new()
+///     .with_border(Border::Chromatic)
+///     .with_mirroring(Mirroring::Horizontal);
+/// ```
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DisplayConfig {
+    /// Border waveform, or `None` for the panel default
+    border: Option<Border>,
+
+    /// Gate scan direction, or `None` for the panel default
+    gate_scan_direction: Option<GateScanDirection>,
+
+    /// Image mirroring
+    mirroring: Mirroring,
+
+    /// Data entry mode, or `None` for the panel default
+    data_entry_mode: Option<DataEntryMode>,
+}
+
+impl DisplayConfig {
+    /// Create a configuration with the panel defaults
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            border: None,
+            gate_scan_direction: None,
+            mirroring: Mirroring::None,
+            data_entry_mode: None,
+        }
+    }
+
+    /// Set the waveform of the border
+    #[must_use]
+    pub const fn with_border(mut self, border: Border) -> Self {
+        self.border = Some(border);
+        self
+    }
+
+    /// Set the scan direction of the gate driver
+    #[must_use]
+    pub const fn with_gate_scan_direction(mut self, direction: GateScanDirection) -> Self {
+        self.gate_scan_direction = Some(direction);
+        self
+    }
+
+    /// Set the mirroring of the image
+    #[must_use]
+    pub const fn with_mirroring(mut self, mirroring: Mirroring) -> Self {
+        self.mirroring = mirroring;
+        self
+    }
+
+    /// Set the direction of RAM address counters
+    #[must_use]
+    pub const fn with_data_entry_mode(mut self, mode: DataEntryMode) -> Self {
+        self.data_entry_mode = Some(mode);
+        self
+    }
+
+    /// Resolve the configuration for a panel
+    pub(crate) fn layout<PANEL: Panel>(self) -> Layout {
+        let [gates_0, gates_1, mut driver_output] = PANEL::DRIVER_OUTPUT_CONTROL;
+        match self.gate_scan_direction {
+            Some(GateScanDirection::Forward) => driver_output &= !GATE_SCAN_DIRECTION_MASK,
+            Some(GateScanDirection::Backward) => driver_output |= GATE_SCAN_DIRECTION_MASK,
+            None => {}
+        }
+
+        let data_entry_mode = self
+            .data_entry_mode
+            .map_or(PANEL::DATA_ENTRY_MODE, DataEntryMode::register);
+
+        Layout {
+            driver_output_control: [gates_0, gates_1, driver_output],
+            data_entry_mode,
+            border_waveform: self.border.map_or(PANEL::BORDER_WAVEFORM, Border::register),
+            line_width: PANEL::LINE_WIDTH,
+            height: PANEL::HEIGHT,
+            columns: Axis {
+                reversed: (PANEL::DATA_ENTRY_MODE & X_INCREMENT_MASK == 0)
+                    != self.mirroring.is_horizontal(),
+                decrement: data_entry_mode & X_INCREMENT_MASK == 0,
+            },
+            rows: Axis {
+                reversed: (PANEL::DATA_ENTRY_MODE & Y_INCREMENT_MASK == 0)
+                    != self.mirroring.is_vertical(),
+                decrement: data_entry_mode & Y_INCREMENT_MASK == 0,
+            },
+            mirrored_bits: self.mirroring.is_horizontal(),
+        }
+    }
+}
+
+/// A configuration resolved for a panel
+///
+/// The layout maps buffer columns and rows to RAM addresses.
+/// By default the first buffer column and row are stored at the start of the
+/// address counters of the panel's data entry mode, and mirroring reverses
+/// that mapping.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Layout {
+    /// Data for the driver output control command
+    pub(crate) driver_output_control: [u8; 3],
+
+    /// Data for the data entry mode command
+    pub(crate) data_entry_mode: u8,
+
+    /// Data for the border waveform control command
+    pub(crate) border_waveform: u8,
+
+    /// Size of a panel line in bytes
+    line_width: usize,
+
+    /// Panel height in pixels
+    height: usize,
+
+    /// Mapping of buffer columns to RAM columns
+    columns: Axis,
+
+    /// Mapping of buffer rows to RAM rows
+    rows: Axis,
+
+    /// Whether the bits of each byte are stored in reverse order
+    mirrored_bits: bool,
+}
+
+/// Mapping of an axis of the buffer to RAM addresses
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Axis {
+    /// Whether the first buffer position is stored at the last RAM address
+    reversed: bool,
+
+    /// Whether the address counter decrements
+    decrement: bool,
+}
+
+impl Axis {
+    /// Convert a buffer position to a RAM address
+    fn address(self, position: usize, size: usize) -> usize {
+        if self.reversed {
+            size - 1 - position
+        } else {
+            position
+        }
+    }
+
+    /// Order two buffer positions as start and end of the address counter
+    fn range(self, first: usize, last: usize, size: usize) -> (usize, usize) {
+        let first = self.address(first, size);
+        let last = self.address(last, size);
+        let (low, high) = if first <= last {
+            (first, last)
+        } else {
+            (last, first)
+        };
+        if self.decrement {
+            (high, low)
+        } else {
+            (low, high)
+        }
+    }
+
+    /// Check whether data must be sent in reverse buffer order
+    fn is_reversed(self) -> bool {
+        self.decrement != self.reversed
+    }
+}
+
+/// A RAM window, with start and end in the direction of address counters
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct RamWindow {
+    /// First RAM column written
+    pub(crate) x_start: u8,
+
+    /// Last RAM column written
+    pub(crate) x_end: u8,
+
+    /// First RAM row written
+    pub(crate) y_start: u16,
+
+    /// Last RAM row written
+    pub(crate) y_end: u16,
+}
+
+impl Layout {
+    /// Convert a window in the buffer to a RAM window
+    ///
+    /// Columns are expressed in bytes and rows in pixels, both inclusive.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn ram_window(
+        &self,
+        first_column: usize,
+        last_column: usize,
+        first_row: usize,
+        last_row: usize,
+    ) -> RamWindow {
+        let (x_start, x_end) = self
+            .columns
+            .range(first_column, last_column, self.line_width);
+        let (y_start, y_end) = self.rows.range(first_row, last_row, self.height);
+
+        RamWindow {
+            x_start: x_start as u8,
+            x_end: x_end as u8,
+            y_start: y_start as u16,
+            y_end: y_end as u16,
+        }
+    }
+
+    /// Convert a window covering the whole panel to a RAM window
+    pub(crate) fn full_ram_window(&self) -> RamWindow {
+        self.ram_window(0, self.line_width - 1, 0, self.height - 1)
+    }
+
+    /// Get the size of a panel line in bytes
+    pub(crate) fn line_width(&self) -> usize {
+        self.line_width
+    }
+
+    /// Check whether the bytes of each line are sent in reverse order
+    pub(crate) fn reversed_lines(&self) -> bool {
+        self.columns.is_reversed()
+    }
+
+    /// Check whether the lines are sent in reverse order
+    pub(crate) fn reversed_rows(&self) -> bool {
+        self.rows.is_reversed()
+    }
+
+    /// Check whether the bits of each byte are sent in reverse order
+    pub(crate) fn mirrored_bits(&self) -> bool {
+        self.mirrored_bits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Epd1in54bV2;
+    use crate::Epd2in13bV4;
+
+    #[test]
+    fn default_layout_uses_panel_registers() {
+        let layout = DisplayConfig::new().layout::<Epd1in54bV2>();
+
+        assert_eq!(layout.driver_output_control, [0xc7, 0x00, 0x01]);
+        assert_eq!(layout.data_entry_mode, 0x01);
+        assert_eq!(layout.border_waveform, 0x05);
+        assert!(!layout.reversed_lines());
+        assert!(!layout.reversed_rows());
+        assert!(!layout.mirrored_bits());
+        assert_eq!(
+            layout.full_ram_window(),
+            RamWindow {
+                x_start: 0x00,
+                x_end: 0x18,
+                y_start: 0xc7,
+                y_end: 0x00,
+            }
+        );
+    }
+
+    #[test]
+    fn registers_are_overridden() {
+        let layout = DisplayConfig::new()
+            .with_border(Border::Chromatic)
+            .with_gate_scan_direction(GateScanDirection::Forward)
+            .with_data_entry_mode(DataEntryMode::IncrementXIncrementY)
+            .layout::<Epd1in54bV2>();
+
+        assert_eq!(layout.driver_output_control, [0xc7, 0x00, 0x00]);
+        assert_eq!(layout.data_entry_mode, 0x03);
+        assert_eq!(layout.border_waveform, 0x06);
+    }
+
+    #[test]
+    fn data_entry_mode_keeps_image() {
+        let layout = DisplayConfig::new()
+            .with_data_entry_mode(DataEntryMode::DecrementXIncrementY)
+            .layout::<Epd1in54bV2>();
+
+        // The first buffer row and column are still stored at RAM row 199
+        // and column 0
+        assert!(layout.reversed_lines());
+        assert!(layout.reversed_rows());
+        assert_eq!(
+            layout.ram_window(0, 1, 0, 1),
+            RamWindow {
+                x_start: 0x01,
+                x_end: 0x00,
+                y_start: 0xc6,
+                y_end: 0xc7,
+            }
+        );
+    }
+
+    #[test]
+    fn mirroring_reverses_mapping() {
+        let layout = DisplayConfig::new()
+            .with_mirroring(Mirroring::Both)
+            .layout::<Epd2in13bV4>();
+
+        assert!(layout.reversed_lines());
+        assert!(layout.reversed_rows());
+        assert!(layout.mirrored_bits());
+        assert_eq!(
+            layout.ram_window(0, 0, 0, 0),
+            RamWindow {
+                x_start: 0x0f,
+                x_end: 0x0f,
+                y_start: 0xf9,
+                y_end: 0xf9,
+            }
+        );
+    }
+}
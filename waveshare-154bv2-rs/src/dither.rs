@@ -0,0 +1,385 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Dithering of RGB images to tri-colors
+
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::Dimensions;
+use embedded_graphics_core::geometry::OriginDimensions;
+use embedded_graphics_core::geometry::Point;
+use embedded_graphics_core::geometry::Size;
+use embedded_graphics_core::image::ImageDrawable;
+use embedded_graphics_core::pixelcolor::Rgb888;
+use embedded_graphics_core::pixelcolor::RgbColor;
+use embedded_graphics_core::primitives::Rectangle;
+use embedded_graphics_core::Pixel;
+
+use crate::color::closest_color;
+use crate::Color;
+
+/// Bayer matrix for ordered dithering
+const BAYER_MATRIX: [[i32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// A dithering method
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Dithering {
+    /// Ordered dithering with a 4×4 Bayer matrix
+    ///
+    /// Each pixel is converted independently, so pixels can be drawn in any
+    /// order.
+    Ordered,
+
+    /// Floyd–Steinberg error diffusion
+    ///
+    /// Errors are diffused to the following pixels, so pixels must be drawn
+    /// row by row, from top to bottom and from left to right.
+    FloydSteinberg,
+}
+
+/// An RGB image converted to tri-colors with dithering
+///
+/// `MAX_WIDTH` is the maximal width of the image for Floyd–Steinberg
+/// dithering, and it defaults to the largest dimension of supported panels.
+/// Errors are diffused through two rows of `MAX_WIDTH` pixels on the stack,
+/// and they are not diffused beyond it.
+///
+/// The adapter can be drawn with `embedded_graphics::image::Image`, like any
+/// other image.
+#[derive(Debug)]
+pub struct DitheredImage<'image, IMAGE, const MAX_WIDTH: usize = 296> {
+    /// Source image
+    image: &'image IMAGE,
+
+    /// Dithering method
+    dithering: Dithering,
+}
+
+impl<'image, IMAGE, const MAX_WIDTH: usize> DitheredImage<'image, IMAGE, MAX_WIDTH>
+where
+    IMAGE: ImageDrawable<Color = Rgb888>,
+{
+    /// Create a new dithered image
+    #[must_use]
+    pub fn new(image: &'image IMAGE, dithering: Dithering) -> Self {
+        Self { image, dithering }
+    }
+}
+
+impl<IMAGE, const MAX_WIDTH: usize> OriginDimensions for DitheredImage<'_, IMAGE, MAX_WIDTH>
+where
+    IMAGE: ImageDrawable<Color = Rgb888>,
+{
+    fn size(&self) -> Size {
+        self.image.size()
+    }
+}
+
+impl<IMAGE, const MAX_WIDTH: usize> ImageDrawable for DitheredImage<'_, IMAGE, MAX_WIDTH>
+where
+    IMAGE: ImageDrawable<Color = Rgb888>,
+{
+    type Color = Color;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let mut target = DitheringTarget::<D, MAX_WIDTH>::new(target, self.dithering);
+        self.image.draw(&mut target)
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let mut target = DitheringTarget::<D, MAX_WIDTH>::new(target, self.dithering);
+        self.image.draw_sub_image(&mut target, area)
+    }
+}
+
+/// A draw target converting RGB pixels to tri-colors
+struct DitheringTarget<'target, D, const MAX_WIDTH: usize> {
+    /// Wrapped target
+    target: &'target mut D,
+
+    /// Dithering state
+    state: DitheringState<MAX_WIDTH>,
+}
+
+impl<'target, D, const MAX_WIDTH: usize> DitheringTarget<'target, D, MAX_WIDTH>
+where
+    D: DrawTarget<Color = Color>,
+{
+    /// Create a new dithering target
+    fn new(target: &'target mut D, dithering: Dithering) -> Self {
+        Self {
+            target,
+            state: DitheringState::new(dithering),
+        }
+    }
+}
+
+impl<D, const MAX_WIDTH: usize> Dimensions for DitheringTarget<'_, D, MAX_WIDTH>
+where
+    D: DrawTarget<Color = Color>,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<D, const MAX_WIDTH: usize> DrawTarget for DitheringTarget<'_, D, MAX_WIDTH>
+where
+    D: DrawTarget<Color = Color>,
+{
+    type Color = Rgb888;
+
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let state = &mut self.state;
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, color)| Pixel(point, state.convert(point, color))),
+        )
+    }
+}
+
+/// State of a dithering conversion
+struct DitheringState<const MAX_WIDTH: usize> {
+    /// Dithering method
+    dithering: Dithering,
+
+    /// Current row
+    row: Option<i32>,
+
+    /// Errors diffused to the current row
+    current_errors: [[i16; 3]; MAX_WIDTH],
+
+    /// Errors diffused to the next row
+    next_errors: [[i16; 3]; MAX_WIDTH],
+}
+
+impl<const MAX_WIDTH: usize> DitheringState<MAX_WIDTH> {
+    /// Create a new dithering state
+    fn new(dithering: Dithering) -> Self {
+        Self {
+            dithering,
+            row: None,
+            current_errors: [[0_i16; 3]; MAX_WIDTH],
+            next_errors: [[0_i16; 3]; MAX_WIDTH],
+        }
+    }
+
+    /// Convert a pixel
+    fn convert(&mut self, point: Point, color: Rgb888) -> Color {
+        let rgb = [color.r(), color.g(), color.b()].map(i32::from);
+        match self.dithering {
+            Dithering::Ordered => convert_ordered(point, rgb),
+            Dithering::FloydSteinberg => self.convert_floyd_steinberg(point, rgb),
+        }
+    }
+
+    /// Convert a pixel with Floyd–Steinberg dithering
+    ///
+    /// Components are clamped after adding errors, so that errors cannot
+    /// accumulate indefinitely.
+    fn convert_floyd_steinberg(&mut self, point: Point, rgb: [i32; 3]) -> Color {
+        self.move_to_row(point.y);
+
+        let Ok(x) = usize::try_from(point.x) else {
+            return closest_color(rgb).0;
+        };
+        let Some(&error) = self.current_errors.get(x) else {
+            return closest_color(rgb).0;
+        };
+
+        let [red, green, blue] = rgb;
+        let [red_error, green_error, blue_error] = error.map(i32::from);
+        let value = [red + red_error, green + green_error, blue + blue_error]
+            .map(|component| component.clamp(0_i32, 255_i32));
+        let (color, components) = closest_color(value);
+        let [red, green, blue] = value;
+        let [closest_red, closest_green, closest_blue] = components;
+        let error = [
+            red - closest_red,
+            green - closest_green,
+            blue - closest_blue,
+        ];
+
+        diffuse(&mut self.current_errors, x.checked_add(1), error, 7);
+        diffuse(&mut self.next_errors, x.checked_sub(1), error, 3);
+        diffuse(&mut self.next_errors, Some(x), error, 5);
+        diffuse(&mut self.next_errors, x.checked_add(1), error, 1);
+
+        color
+    }
+
+    /// Move to a row, discarding errors if rows are not consecutive
+    fn move_to_row(&mut self, row: i32) {
+        match self.row {
+            Some(current) if current == row => {}
+            Some(current) if current + 1_i32 == row => {
+                self.current_errors = self.next_errors;
+                self.next_errors = [[0_i16; 3]; MAX_WIDTH];
+            }
+            _ => {
+                self.current_errors = [[0_i16; 3]; MAX_WIDTH];
+                self.next_errors = [[0_i16; 3]; MAX_WIDTH];
+            }
+        }
+        self.row = Some(row);
+    }
+}
+
+/// Convert a pixel with ordered dithering
+#[allow(clippy::cast_sign_loss)]
+fn convert_ordered(point: Point, rgb: [i32; 3]) -> Color {
+    let threshold = BAYER_MATRIX[point.y.rem_euclid(4) as usize][point.x.rem_euclid(4) as usize];
+    let offset = threshold * 16_i32 + 8_i32 - 128_i32;
+    let (color, _) = closest_color(rgb.map(|component| component + offset));
+    color
+}
+
+/// Diffuse a fraction of an error to a pixel
+///
+/// The error is multiplied by `weight` ÷ 16.
+/// Errors of clamped components never exceed 255, so their fractions fit in
+/// 16 bits.
+#[allow(clippy::cast_possible_truncation)]
+fn diffuse(errors: &mut [[i16; 3]], x: Option<usize>, error: [i32; 3], weight: i32) {
+    if let Some(target) = x.and_then(|x| errors.get_mut(x)) {
+        for (target, error) in target.iter_mut().zip(error) {
+            *target = target.saturating_add((error * weight / 16) as i16);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Buffer;
+
+    /// A test buffer
+    type TestBuffer = Buffer<16, 8, 16>;
+
+    /// An image of a single RGB color
+    struct SolidImage(Rgb888);
+
+    impl OriginDimensions for SolidImage {
+        fn size(&self) -> Size {
+            Size::new(16, 8)
+        }
+    }
+
+    impl ImageDrawable for SolidImage {
+        type Color = Rgb888;
+
+        fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+        where
+            D: DrawTarget<Color = Self::Color>,
+        {
+            target.fill_solid(&self.bounding_box(), self.0)
+        }
+
+        fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+        where
+            D: DrawTarget<Color = Self::Color>,
+        {
+            target.fill_solid(&Rectangle::new(Point::zero(), area.size), self.0)
+        }
+    }
+
+    /// Draw a dithered image of a single color
+    fn draw_dithered(color: Rgb888, dithering: Dithering) -> TestBuffer {
+        let mut buffer = TestBuffer::new();
+        let image = SolidImage(color);
+        let Ok(()) = DitheredImage::<_, 16>::new(&image, dithering).draw(&mut buffer);
+        buffer
+    }
+
+    /// Count black and chromatic pixels
+    fn count_pixels(buffer: &TestBuffer) -> (u32, u32) {
+        let black = buffer
+            .black_buffer()
+            .iter()
+            .map(|byte| byte.count_zeros())
+            .sum();
+        let chromatic = buffer
+            .chromatic_buffer()
+            .iter()
+            .map(|byte| byte.count_zeros())
+            .sum();
+        (black, chromatic)
+    }
+
+    #[test]
+    fn convert_rgb() {
+        assert_eq!(Color::from(Rgb888::new(0, 0, 0)), Color::Black);
+        assert_eq!(Color::from(Rgb888::new(255, 255, 255)), Color::White);
+        assert_eq!(Color::from(Rgb888::new(255, 0, 0)), Color::Chromatic);
+        assert_eq!(Color::from(Rgb888::new(200, 40, 30)), Color::Chromatic);
+        assert_eq!(Color::from(Rgb888::new(60, 60, 60)), Color::Black);
+        assert_eq!(Color::from(Rgb888::new(200, 200, 200)), Color::White);
+    }
+
+    #[test]
+    fn ordered_gray() {
+        let buffer = draw_dithered(Rgb888::new(128, 128, 128), Dithering::Ordered);
+
+        let (black, chromatic) = count_pixels(&buffer);
+        assert_eq!(black, 64);
+        assert_eq!(chromatic, 0);
+    }
+
+    #[test]
+    fn floyd_steinberg_gray() {
+        let buffer = draw_dithered(Rgb888::new(128, 128, 128), Dithering::FloydSteinberg);
+
+        let (black, chromatic) = count_pixels(&buffer);
+        assert!((56..=72).contains(&black), "{black} black pixels");
+        assert_eq!(chromatic, 0);
+    }
+
+    #[test]
+    fn floyd_steinberg_pure_colors() {
+        let buffer = draw_dithered(Rgb888::new(255, 0, 0), Dithering::FloydSteinberg);
+        assert_eq!(count_pixels(&buffer), (0, 128));
+
+        let buffer = draw_dithered(Rgb888::new(255, 255, 255), Dithering::FloydSteinberg);
+        assert_eq!(count_pixels(&buffer), (0, 0));
+    }
+
+    #[test]
+    fn dark_red_mixes_black_and_chromatic() {
+        for dithering in [Dithering::Ordered, Dithering::FloydSteinberg] {
+            let buffer = draw_dithered(Rgb888::new(128, 0, 0), dithering);
+
+            let (black, chromatic) = count_pixels(&buffer);
+            assert!(black > 0 && chromatic > 0, "{dithering:?}");
+            assert_eq!(black + chromatic, 128, "{dithering:?}");
+        }
+    }
+
+    #[test]
+    fn sub_image_covers_area() {
+        let mut buffer = TestBuffer::new();
+        let image = SolidImage(Rgb888::new(0, 0, 0));
+        let area = Rectangle::new(Point::new(2, 2), Size::new(4, 3));
+        let Ok(()) = DitheredImage::<_, 16>::new(&image, Dithering::Ordered)
+            .draw_sub_image(&mut buffer, &area);
+
+        let (black, _) = count_pixels(&buffer);
+        assert_eq!(black, 12);
+    }
+}
@@ -19,6 +19,8 @@
 
 /// An error
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[allow(clippy::error_impl_error)]
 pub enum Error {
     #[cfg(any(feature = "async", feature = "blocking"))]
     /// An error in the underlying SPI bus
@@ -27,6 +29,26 @@ pub enum Error {
     #[cfg(any(feature = "async", feature = "blocking"))]
     /// An error in the underlying digital system
     Digital(DigitalErrorKind),
+
+    #[cfg(any(feature = "async", feature = "blocking"))]
+    /// The display was still busy when the timeout expired
+    BusyTimeout,
+
+    #[cfg(any(feature = "async", feature = "blocking"))]
+    /// The display was not initialized
+    Uninitialized,
+
+    #[cfg(any(feature = "async", feature = "blocking"))]
+    /// The display is in deep sleep
+    Asleep,
+
+    #[cfg(all(feature = "draw-target", any(feature = "async", feature = "blocking")))]
+    /// A buffer or strip does not match the panel geometry
+    BufferSize,
+
+    #[cfg(any(feature = "async", feature = "blocking"))]
+    /// A compressed image is malformed or does not match the panel geometry
+    InvalidImage,
 }
 
 #[cfg(any(feature = "async", feature = "blocking"))]
@@ -0,0 +1,338 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Export of buffers to image files
+//!
+//! Images have the buffer's rotated size, so they look like the display in
+//! its mounting orientation.
+
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use std::io;
+use std::io::Write;
+
+use embedded_graphics_core::geometry::OriginDimensions;
+use embedded_graphics_core::geometry::Point;
+use embedded_graphics_core::geometry::Size;
+use embedded_graphics_core::pixelcolor::Rgb888;
+use embedded_graphics_core::pixelcolor::RgbColor;
+
+use crate::Buffer;
+use crate::Color;
+
+/// PNG file signature
+const PNG_SIGNATURE: [u8; 8] = [0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a];
+
+/// Maximal size of an uncompressed deflate block
+const MAX_STORED_BLOCK_SIZE: usize = 0xffff;
+
+/// A color plane of a buffer
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ColorPlane {
+    /// Black plane
+    Black,
+
+    /// Chromatic plane
+    Chromatic,
+}
+
+/// Write a color plane of a buffer as a binary PBM image
+///
+/// Pixels of the plane's color are black in the image, and all other pixels
+/// are white.
+///
+/// # Errors
+///
+/// Returns an error if writing fails
+pub fn write_pbm<WRITER, const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize>(
+    buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+    plane: ColorPlane,
+    mut writer: WRITER,
+) -> io::Result<()>
+where
+    WRITER: Write,
+{
+    let target = match plane {
+        ColorPlane::Black => Color::Black,
+        ColorPlane::Chromatic => Color::Chromatic,
+    };
+
+    let Size { width, height } = buffer.size();
+    write!(writer, "P4\n{width} {height}\n")?;
+
+    let mut line = vec![0x00; width.div_ceil(8) as usize];
+    for row in 0..height {
+        line.fill(0x00);
+        for column in 0..width {
+            if color_at(buffer, column, row) == target {
+                line[column as usize / 8] |= 0b1000_0000 >> (column % 8);
+            }
+        }
+        writer.write_all(&line)?;
+    }
+
+    Ok(())
+}
+
+/// Write a buffer as a binary PPM image
+///
+/// # Errors
+///
+/// Returns an error if writing fails
+pub fn write_ppm<WRITER, const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize>(
+    buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+    mut writer: WRITER,
+) -> io::Result<()>
+where
+    WRITER: Write,
+{
+    let Size { width, height } = buffer.size();
+    write!(writer, "P6\n{width} {height}\n255\n")?;
+
+    for row in 0..height {
+        for column in 0..width {
+            writer.write_all(&rgb_at(buffer, column, row))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a buffer as a PNG image
+///
+/// Image data is stored without compression.
+///
+/// # Errors
+///
+/// Returns an error if writing fails
+pub fn write_png<WRITER, const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize>(
+    buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+    mut writer: WRITER,
+) -> io::Result<()>
+where
+    WRITER: Write,
+{
+    let Size { width, height } = buffer.size();
+
+    let mut scanlines = Vec::with_capacity(height as usize * (1 + 3 * width as usize));
+    for row in 0..height {
+        // Filter type none
+        scanlines.push(0x00);
+        for column in 0..width {
+            scanlines.extend_from_slice(&rgb_at(buffer, column, row));
+        }
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits RGB, default compression and filtering, no interlacing
+    header.extend_from_slice(&[0x08, 0x02, 0x00, 0x00, 0x00]);
+
+    writer.write_all(&PNG_SIGNATURE)?;
+    write_png_chunk(&mut writer, *b"IHDR", &header)?;
+    write_png_chunk(&mut writer, *b"IDAT", &zlib_stored(&scanlines))?;
+    write_png_chunk(&mut writer, *b"IEND", &[])?;
+
+    Ok(())
+}
+
+/// Get the color of a pixel, or white if it lies outside of the buffer
+#[allow(clippy::cast_possible_wrap)]
+fn color_at<const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize>(
+    buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+    column: u32,
+    row: u32,
+) -> Color {
+    buffer
+        .pixel(Point::new(column as i32, row as i32))
+        .unwrap_or(Color::White)
+}
+
+/// Get the RGB components of a pixel
+fn rgb_at<const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize>(
+    buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+    column: u32,
+    row: u32,
+) -> [u8; 3] {
+    let color = Rgb888::from(color_at(buffer, column, row));
+    [color.r(), color.g(), color.b()]
+}
+
+/// Write a PNG chunk
+///
+/// # Errors
+///
+/// Returns an error if writing fails, or if the chunk is too large
+fn write_png_chunk<WRITER: Write>(
+    writer: &mut WRITER,
+    kind: [u8; 4],
+    data: &[u8],
+) -> io::Result<()> {
+    let length = u32::try_from(data.len()).map_err(io::Error::other)?;
+    writer.write_all(&length.to_be_bytes())?;
+    writer.write_all(&kind)?;
+    writer.write_all(data)?;
+    writer.write_all(&crc32(kind.iter().chain(data)).to_be_bytes())
+}
+
+/// Wrap data in a zlib stream of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let blocks = data.len().div_ceil(MAX_STORED_BLOCK_SIZE).max(1);
+    let mut stream = Vec::with_capacity(data.len() + 5 * blocks + 6);
+
+    // Deflate with a 32 KiB window, no dictionary and fastest compression
+    stream.extend_from_slice(&[0x78, 0x01]);
+
+    let mut chunks = data.chunks(MAX_STORED_BLOCK_SIZE).peekable();
+    loop {
+        let chunk = chunks.next().unwrap_or_default();
+        let is_final = chunks.peek().is_none();
+        let length = u16::try_from(chunk.len()).unwrap_or(u16::MAX);
+
+        stream.push(u8::from(is_final));
+        stream.extend_from_slice(&length.to_le_bytes());
+        stream.extend_from_slice(&(!length).to_le_bytes());
+        stream.extend_from_slice(chunk);
+
+        if is_final {
+            break;
+        }
+    }
+
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+/// Compute the CRC-32 checksum used by PNG
+fn crc32<'data>(data: impl IntoIterator<Item = &'data u8>) -> u32 {
+    let mut crc = !0_u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8_u8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1_u32) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Compute the Adler-32 checksum used by zlib
+fn adler32(data: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+
+    let (first, second) = data.iter().fold((1_u32, 0_u32), |(first, second), byte| {
+        let first = (first + u32::from(*byte)) % MODULUS;
+        (first, (second + first) % MODULUS)
+    });
+    (second << 16) | first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use embedded_graphics_core::draw_target::DrawTarget;
+    use embedded_graphics_core::Pixel;
+
+    use crate::Rotation;
+
+    /// A small non-square buffer
+    type TestBuffer = Buffer<3, 2, 2>;
+
+    /// Create a buffer with one pixel of each color on the first row
+    fn buffer() -> TestBuffer {
+        let mut buffer = TestBuffer::new();
+        let Ok(()) = buffer.draw_iter([
+            Pixel(Point::new(0, 0), Color::Black),
+            Pixel(Point::new(1, 0), Color::Chromatic),
+        ]);
+        buffer
+    }
+
+    #[test]
+    fn pbm_planes() {
+        let mut black = Vec::new();
+        assert!(write_pbm(&buffer(), ColorPlane::Black, &mut black).is_ok());
+        assert_eq!(black, b"P4\n3 2\n\x80\x00");
+
+        let mut chromatic = Vec::new();
+        assert!(write_pbm(&buffer(), ColorPlane::Chromatic, &mut chromatic).is_ok());
+        assert_eq!(chromatic, b"P4\n3 2\n\x40\x00");
+    }
+
+    #[test]
+    fn ppm_colors() {
+        let mut ppm = Vec::new();
+        assert!(write_ppm(&buffer(), &mut ppm).is_ok());
+
+        let mut expected = b"P6\n3 2\n255\n".to_vec();
+        expected.extend_from_slice(&[0, 0, 0, 255, 0, 0, 255, 255, 255]);
+        expected.extend_from_slice(&[255; 9]);
+        assert_eq!(ppm, expected);
+    }
+
+    #[test]
+    fn ppm_is_rotated() {
+        let mut buffer = buffer();
+        buffer.set_rotation(Rotation::Rotate90);
+
+        let mut ppm = Vec::new();
+        assert!(write_ppm(&buffer, &mut ppm).is_ok());
+        assert!(ppm.starts_with(b"P6\n2 3\n255\n"));
+    }
+
+    #[test]
+    fn png_structure() {
+        let mut png = Vec::new();
+        assert!(write_png(&buffer(), &mut png).is_ok());
+
+        let scanlines = [
+            [0, 0, 0, 0, 255, 0, 0, 255, 255, 255],
+            [0, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ]
+        .concat();
+        let mut idat = vec![0x78, 0x01, 0x01, 20, 0, !20, 0xff];
+        idat.extend_from_slice(&scanlines);
+        idat.extend_from_slice(&adler32(&scanlines).to_be_bytes());
+
+        let header = [0, 0, 0, 3, 0, 0, 0, 2, 8, 2, 0, 0, 0];
+
+        let mut expected = PNG_SIGNATURE.to_vec();
+        expected.extend_from_slice(&[0, 0, 0, 13]);
+        expected.extend_from_slice(b"IHDR");
+        expected.extend_from_slice(&header);
+        expected.extend_from_slice(&crc32(b"IHDR".iter().chain(&header)).to_be_bytes());
+        expected.extend_from_slice(&[0, 0, 0, 31]);
+        expected.extend_from_slice(b"IDAT");
+        expected.extend_from_slice(&idat);
+        expected.extend_from_slice(&crc32(b"IDAT".iter().chain(&idat)).to_be_bytes());
+        expected.extend_from_slice(&[0, 0, 0, 0]);
+        expected.extend_from_slice(b"IEND");
+        expected.extend_from_slice(&[0xae, 0x42, 0x60, 0x82]);
+        assert_eq!(png, expected);
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn zlib_stream_of_empty_data() {
+        assert_eq!(
+            zlib_stored(&[]),
+            [0x78, 0x01, 0x01, 0x00, 0x00, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01]
+        );
+    }
+}
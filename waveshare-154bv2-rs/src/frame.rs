@@ -0,0 +1,384 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Memory of the last frame sent to a display
+
+use crate::Buffer;
+use crate::Epd1in54bV2;
+use crate::Panel;
+use crate::Planes;
+use crate::RefreshMode;
+
+/// A rectangular area of a color plane
+///
+/// Columns are expressed in bytes and rows in pixels, both inclusive.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Window {
+    /// First column
+    pub(crate) first_column: usize,
+
+    /// Last column
+    pub(crate) last_column: usize,
+
+    /// First row
+    pub(crate) first_row: usize,
+
+    /// Last row
+    pub(crate) last_row: usize,
+}
+
+/// Changes between a new frame and the last one
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Changes {
+    /// Nothing changed
+    None,
+
+    /// Whole planes must be transferred
+    All,
+
+    /// Only the dirty area of each plane must be transferred
+    Windows {
+        /// Dirty area of the black plane
+        black: Option<Window>,
+
+        /// Dirty area of the chromatic plane
+        chromatic: Option<Window>,
+    },
+}
+
+/// Generation of the content of a display
+///
+/// The protocol counts writes to display RAM and resets of the display, and
+/// a frame remembers the generation it was shown at, so that it is no longer
+/// trusted once the display was updated in any other way.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Generation {
+    /// Number of writes to display RAM
+    writes: u32,
+
+    /// Number of resets of the display, each losing its RAM
+    resets: u32,
+}
+
+impl Generation {
+    /// Count a write to display RAM
+    ///
+    /// This must be called before writing, so that the frame is not trusted
+    /// if the following refresh fails.
+    pub(crate) fn write(&mut self) {
+        self.writes = self.writes.wrapping_add(1);
+    }
+
+    /// Count a reset of the display
+    ///
+    /// The image on the display is retained, but its RAM is lost.
+    pub(crate) fn reset(&mut self) {
+        self.resets = self.resets.wrapping_add(1);
+    }
+}
+
+/// The last frame sent to a display
+///
+/// A frame is owned by the caller and passed to the `draw_buffer_changes()`
+/// method of a display, which compares buffers against it to skip unchanged
+/// ones and to transfer only the areas that changed.
+/// It stores a copy of both color planes, so it takes as much memory as a
+/// [`Buffer`].
+///
+/// A frame must only be used with a single display.
+/// It is no longer trusted after the display is updated without it, so the
+/// following update transfers whole planes.
+pub struct Frame<PANEL: Panel = Epd1in54bV2> {
+    /// Black plane
+    black: PANEL::Plane,
+
+    /// Chromatic plane
+    chromatic: PANEL::Plane,
+
+    /// Generation of the display when the frame was shown, if ever
+    shown: Option<Generation>,
+
+    /// Flag for a frame fully stored in display RAM when it was shown
+    in_ram: bool,
+}
+
+impl<PANEL: Panel> Frame<PANEL> {
+    /// Create an empty frame
+    ///
+    /// The frame was never shown, so the first update transfers whole planes.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            black: PANEL::BLANK_PLANE,
+            chromatic: PANEL::BLANK_PLANE,
+            shown: None,
+            in_ram: false,
+        }
+    }
+
+    /// Compute changes from this frame to new planes
+    ///
+    /// When the chromatic plane is `None`, only the black plane is compared.
+    pub(crate) fn changes(
+        &self,
+        black: &[u8],
+        chromatic: Option<&[u8]>,
+        generation: Generation,
+    ) -> Changes {
+        let Some(shown) = self.shown.filter(|shown| shown.writes == generation.writes) else {
+            return Changes::All;
+        };
+
+        let black = dirty_window(self.black.as_ref(), black, PANEL::LINE_WIDTH);
+        let chromatic = chromatic.and_then(|chromatic| {
+            dirty_window(self.chromatic.as_ref(), chromatic, PANEL::LINE_WIDTH)
+        });
+
+        if black.is_none() && chromatic.is_none() {
+            Changes::None
+        } else if self.in_ram && shown.resets == generation.resets {
+            Changes::Windows { black, chromatic }
+        } else {
+            Changes::All
+        }
+    }
+
+    /// Store planes shown on the display after a refresh
+    ///
+    /// When the chromatic plane is `None`, tri-color panels were refreshed
+    /// without it, so the display shows no chromatic pixel and its RAM no
+    /// longer matches the frame.
+    /// Black/white panels have no chromatic plane, so their RAM still matches
+    /// the frame.
+    pub(crate) fn store(&mut self, black: &[u8], chromatic: Option<&[u8]>, generation: Generation) {
+        self.black.as_mut().copy_from_slice(black);
+        if let Some(chromatic) = chromatic {
+            self.chromatic.as_mut().copy_from_slice(chromatic);
+            self.in_ram = true;
+        } else if PANEL::PLANES == Planes::TriColor {
+            self.chromatic = PANEL::BLANK_PLANE;
+            self.in_ram = false;
+        } else {
+            self.in_ram = true;
+        }
+        self.shown = Some(generation);
+    }
+}
+
+impl<PANEL: Panel> Default for Frame<PANEL> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Select the planes of a buffer that are transferred for a refresh mode
+///
+/// The chromatic plane is only transferred to tri-color panels, and not with
+/// [`RefreshMode::Fast`].
+pub(crate) fn planes_to_transfer<
+    PANEL: Panel,
+    const WIDTH: usize,
+    const HEIGHT: usize,
+    const BYTE_SIZE: usize,
+>(
+    buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+    mode: RefreshMode,
+) -> (&[u8], Option<&[u8]>) {
+    let chromatic = (PANEL::PLANES == Planes::TriColor && mode != RefreshMode::Fast)
+        .then(|| buffer.chromatic_buffer());
+    (buffer.black_buffer(), chromatic)
+}
+
+/// Find the smallest window containing all differences between two planes
+///
+/// Return `None` if the planes are identical.
+pub(crate) fn dirty_window(previous: &[u8], current: &[u8], line_width: usize) -> Option<Window> {
+    let mut window: Option<Window> = None;
+
+    let rows = previous.chunks(line_width).zip(current.chunks(line_width));
+    for (row, (previous, current)) in rows.enumerate() {
+        let differs = |(previous, current): (&u8, &u8)| previous != current;
+        let Some(first_column) = previous.iter().zip(current).position(differs) else {
+            continue;
+        };
+        let last_column = previous
+            .iter()
+            .zip(current)
+            .rposition(differs)
+            .unwrap_or(first_column);
+
+        window = Some(match window {
+            None => Window {
+                first_column,
+                last_column,
+                first_row: row,
+                last_row: row,
+            },
+            Some(window) => Window {
+                first_column: window.first_column.min(first_column),
+                last_column: window.last_column.max(last_column),
+                first_row: window.first_row,
+                last_row: row,
+            },
+        });
+    }
+
+    window
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Epd1in54V2;
+    use crate::Epd1in54bV2;
+    use crate::Epd2in13bV4;
+    use crate::Epd2in9bV4;
+
+    /// Build a 1.54 in plane with some bytes changed
+    fn plane_with_changes(changes: &[(usize, usize)]) -> [u8; 5000] {
+        let mut plane = [0xff; 5000];
+        for &(column, row) in changes {
+            plane[row * 25 + column] = 0x00;
+        }
+        plane
+    }
+
+    #[test]
+    fn blank_planes_match_panels() {
+        assert_eq!(
+            Epd1in54bV2::BLANK_PLANE.len(),
+            <Epd1in54bV2 as Panel>::BYTE_SIZE
+        );
+        assert_eq!(
+            Epd1in54V2::BLANK_PLANE.len(),
+            <Epd1in54V2 as Panel>::BYTE_SIZE
+        );
+        assert_eq!(
+            Epd2in13bV4::BLANK_PLANE.len(),
+            <Epd2in13bV4 as Panel>::BYTE_SIZE
+        );
+        assert_eq!(
+            Epd2in9bV4::BLANK_PLANE.len(),
+            <Epd2in9bV4 as Panel>::BYTE_SIZE
+        );
+    }
+
+    #[test]
+    fn dirty_window_of_identical_planes() {
+        let plane = plane_with_changes(&[(3, 4)]);
+
+        assert_eq!(dirty_window(&plane, &plane, 25), None);
+    }
+
+    #[test]
+    fn dirty_window_spans_all_changes() {
+        let previous = plane_with_changes(&[]);
+        let current = plane_with_changes(&[(7, 10), (2, 12), (9, 12), (5, 30)]);
+
+        assert_eq!(
+            dirty_window(&previous, &current, 25),
+            Some(Window {
+                first_column: 2,
+                last_column: 9,
+                first_row: 10,
+                last_row: 30,
+            })
+        );
+    }
+
+    #[test]
+    fn changes_of_shown_frame() {
+        let mut generation = Generation::default();
+        let mut frame = Frame::<Epd1in54bV2>::new();
+        let black = plane_with_changes(&[(1, 1)]);
+        let chromatic = plane_with_changes(&[]);
+        assert_eq!(
+            frame.changes(&black, Some(&chromatic), generation),
+            Changes::All
+        );
+
+        generation.write();
+        frame.store(&black, Some(&chromatic), generation);
+        assert_eq!(
+            frame.changes(&black, Some(&chromatic), generation),
+            Changes::None
+        );
+
+        let new_chromatic = plane_with_changes(&[(4, 8)]);
+        assert_eq!(
+            frame.changes(&black, Some(&new_chromatic), generation),
+            Changes::Windows {
+                black: None,
+                chromatic: Some(Window {
+                    first_column: 4,
+                    last_column: 4,
+                    first_row: 8,
+                    last_row: 8,
+                }),
+            }
+        );
+
+        generation.reset();
+        assert_eq!(
+            frame.changes(&black, Some(&chromatic), generation),
+            Changes::None
+        );
+        assert_eq!(
+            frame.changes(&black, Some(&new_chromatic), generation),
+            Changes::All
+        );
+
+        generation.write();
+        assert_eq!(
+            frame.changes(&black, Some(&chromatic), generation),
+            Changes::All
+        );
+    }
+
+    #[test]
+    fn store_without_chromatic_on_tri_color_panel() {
+        let generation = Generation::default();
+        let mut frame = Frame::<Epd1in54bV2>::new();
+        let black = plane_with_changes(&[]);
+        let chromatic = plane_with_changes(&[(4, 8)]);
+        frame.store(&black, None, generation);
+
+        assert_eq!(frame.changes(&black, None, generation), Changes::None);
+        assert_eq!(
+            frame.changes(&plane_with_changes(&[(1, 1)]), None, generation),
+            Changes::All
+        );
+        assert_eq!(
+            frame.changes(&black, Some(&chromatic), generation),
+            Changes::All
+        );
+    }
+
+    #[test]
+    fn store_without_chromatic_on_black_white_panel() {
+        let generation = Generation::default();
+        let mut frame = Frame::<Epd1in54V2>::new();
+        let previous = plane_with_changes(&[]);
+        frame.store(&previous, None, generation);
+
+        let current = plane_with_changes(&[(20, 100)]);
+        assert_eq!(
+            frame.changes(&current, None, generation),
+            Changes::Windows {
+                black: Some(Window {
+                    first_column: 20,
+                    last_column: 20,
+                    first_row: 100,
+                    last_row: 100,
+                }),
+                chromatic: None,
+            }
+        );
+    }
+}
@@ -6,23 +6,102 @@
 // https://opensource.org/licenses/MIT
 // https://opensource.org/licenses/Apache-2.0
 
-//! Interface to WaveShare 1.54inches B v2 E-INK display
+//! Interface to WaveShare E-INK displays based on SSD1680/SSD1681 controllers
+//!
+//! Originally written for the WaveShare 1.54inches B v2 display, other panels
+//! are supported through the [`Panel`] trait.
 
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 #[cfg(feature = "async")]
 mod r#async;
+#[cfg(all(feature = "async", feature = "draw-target"))]
+pub use self::r#async::BufferedDisplay as AsyncBufferedDisplay;
 #[cfg(feature = "async")]
 pub use self::r#async::Display as AsyncDisplay;
+#[cfg(feature = "async")]
+pub use self::r#async::RefreshHandle as AsyncRefreshHandle;
 
 #[cfg(feature = "blocking")]
 mod blocking;
+#[cfg(all(feature = "blocking", feature = "draw-target"))]
+pub use self::blocking::BufferedDisplay;
 #[cfg(feature = "blocking")]
 pub use self::blocking::Display;
+#[cfg(feature = "blocking")]
+pub use self::blocking::RefreshHandle;
 
 #[cfg(any(feature = "async", feature = "blocking"))]
 mod command;
 
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod config;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::config::Border;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::config::DataEntryMode;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::config::DisplayConfig;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::config::GateScanDirection;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::config::Mirroring;
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod panel;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::panel::Epd1in54V2;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::panel::Epd1in54bV2;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::panel::Epd2in13bV4;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::panel::Epd2in9bV4;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::panel::Panel;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::panel::Planes;
+
+#[cfg(all(feature = "draw-target", any(feature = "async", feature = "blocking")))]
+mod frame;
+#[cfg(all(feature = "draw-target", any(feature = "async", feature = "blocking")))]
+pub use self::frame::Frame;
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod protocol;
+
+#[cfg(all(test, any(feature = "async", feature = "blocking")))]
+mod transcript;
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod refresh;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::refresh::RefreshMode;
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod rle;
+#[cfg(all(
+    feature = "std",
+    feature = "draw-target",
+    any(feature = "async", feature = "blocking")
+))]
+pub use self::rle::compress_buffer;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::rle::CompressedImage;
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod waveform;
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod write;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::write::WriteStrategy;
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod power;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use self::power::PowerState;
+
 #[cfg(feature = "draw-target")]
 mod buffer;
 #[cfg(feature = "draw-target")]
@@ -30,13 +109,46 @@
 #[cfg(feature = "draw-target")]
 pub use self::buffer::Epd1in54Buffer;
 #[cfg(feature = "draw-target")]
+pub use self::buffer::Epd2in13Buffer;
+#[cfg(feature = "draw-target")]
+pub use self::buffer::Epd2in9Buffer;
+#[cfg(feature = "draw-target")]
 pub use self::buffer::Rotation;
 
+#[cfg(feature = "draw-target")]
+mod strip;
+#[cfg(feature = "draw-target")]
+pub use self::strip::Epd1in54Strip;
+#[cfg(feature = "draw-target")]
+pub use self::strip::Epd2in13Strip;
+#[cfg(feature = "draw-target")]
+pub use self::strip::Epd2in9Strip;
+#[cfg(feature = "draw-target")]
+pub use self::strip::Strip;
+
 #[cfg(feature = "draw-target")]
 mod color;
 #[cfg(feature = "draw-target")]
 pub use self::color::Color;
 
+#[cfg(feature = "draw-target")]
+mod dither;
+#[cfg(feature = "draw-target")]
+pub use self::dither::DitheredImage;
+#[cfg(feature = "draw-target")]
+pub use self::dither::Dithering;
+
+#[cfg(all(feature = "std", feature = "draw-target"))]
+mod export;
+#[cfg(all(feature = "std", feature = "draw-target"))]
+pub use self::export::write_pbm;
+#[cfg(all(feature = "std", feature = "draw-target"))]
+pub use self::export::write_png;
+#[cfg(all(feature = "std", feature = "draw-target"))]
+pub use self::export::write_ppm;
+#[cfg(all(feature = "std", feature = "draw-target"))]
+pub use self::export::ColorPlane;
+
 #[cfg(any(feature = "async", feature = "blocking", feature = "draw-target"))]
 mod error;
 #[cfg(any(feature = "async", feature = "blocking", feature = "draw-target"))]
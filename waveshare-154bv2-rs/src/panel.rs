@@ -0,0 +1,209 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Descriptions of supported panels
+
+use crate::command;
+
+#[cfg(feature = "draw-target")]
+use crate::Error;
+
+/// Color planes supported by a panel
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Planes {
+    /// Only black and white
+    BlackWhite,
+
+    /// Black, white and a chromatic color
+    TriColor,
+}
+
+/// A description of an E-INK panel driven by a SSD1680/SSD1681 controller
+pub trait Panel {
+    /// Panel width in pixels
+    const WIDTH: usize;
+
+    /// Panel height in pixels
+    const HEIGHT: usize;
+
+    /// Size of a panel line in bytes
+    const LINE_WIDTH: usize = Self::WIDTH.div_ceil(8);
+
+    /// Size of a color plane in bytes
+    const BYTE_SIZE: usize = Self::LINE_WIDTH * Self::HEIGHT;
+
+    /// Color planes supported by the panel
+    const PLANES: Planes;
+
+    /// Data for the driver output control command
+    const DRIVER_OUTPUT_CONTROL: [u8; 3];
+
+    /// Data for the data entry mode command
+    const DATA_ENTRY_MODE: u8;
+
+    /// Data for the border waveform control command
+    const BORDER_WAVEFORM: u8;
+
+    /// Data for the display update control 1 command
+    const DISPLAY_UPDATE_CONTROL_1: [u8; 2];
+
+    /// Additional commands sent at the end of initialization
+    const INITIALIZATION: &'static [(u8, &'static [u8])];
+
+    /// Display update sequence for a full refresh
+    const FULL_REFRESH: u8;
+
+    /// Display update sequence for a partial refresh
+    const PARTIAL_REFRESH: u8;
+
+    #[cfg(feature = "draw-target")]
+    /// Storage for a color plane of [`Self::BYTE_SIZE`] bytes
+    type Plane: AsRef<[u8]> + AsMut<[u8]>;
+
+    #[cfg(feature = "draw-target")]
+    /// A color plane with all bits set
+    const BLANK_PLANE: Self::Plane;
+}
+
+/// WaveShare 1.54inches B v2 panel (200×200, black/white/red)
+#[derive(Debug)]
+pub struct Epd1in54bV2;
+
+impl Panel for Epd1in54bV2 {
+    const WIDTH: usize = 200;
+    const HEIGHT: usize = 200;
+    const PLANES: Planes = Planes::TriColor;
+    const DRIVER_OUTPUT_CONTROL: [u8; 3] = [0xc7, 0x00, 0x01];
+    const DATA_ENTRY_MODE: u8 = 0x01;
+    const BORDER_WAVEFORM: u8 = 0x05;
+    const DISPLAY_UPDATE_CONTROL_1: [u8; 2] = [0x00, 0x00];
+    const INITIALIZATION: &'static [(u8, &'static [u8])] = &[];
+    const FULL_REFRESH: u8 = 0xf7;
+    const PARTIAL_REFRESH: u8 = 0xff;
+
+    #[cfg(feature = "draw-target")]
+    type Plane = [u8; 5000];
+
+    #[cfg(feature = "draw-target")]
+    const BLANK_PLANE: Self::Plane = [0xff; 5000];
+}
+
+/// WaveShare 1.54inches v2 panel (200×200, black/white)
+#[derive(Debug)]
+pub struct Epd1in54V2;
+
+impl Panel for Epd1in54V2 {
+    const WIDTH: usize = 200;
+    const HEIGHT: usize = 200;
+    const PLANES: Planes = Planes::BlackWhite;
+    const DRIVER_OUTPUT_CONTROL: [u8; 3] = [0xc7, 0x00, 0x01];
+    const DATA_ENTRY_MODE: u8 = 0x01;
+    const BORDER_WAVEFORM: u8 = 0x01;
+    const DISPLAY_UPDATE_CONTROL_1: [u8; 2] = [0x00, 0x00];
+    const INITIALIZATION: &'static [(u8, &'static [u8])] =
+        &[(command::TEMPERATURE_SENSOR_CONTROL, &[0x80])];
+    const FULL_REFRESH: u8 = 0xf7;
+    const PARTIAL_REFRESH: u8 = 0xff;
+
+    #[cfg(feature = "draw-target")]
+    type Plane = [u8; 5000];
+
+    #[cfg(feature = "draw-target")]
+    const BLANK_PLANE: Self::Plane = [0xff; 5000];
+}
+
+/// WaveShare 2.13inches B v4 panel (122×250, black/white/red)
+#[derive(Debug)]
+pub struct Epd2in13bV4;
+
+impl Panel for Epd2in13bV4 {
+    const WIDTH: usize = 122;
+    const HEIGHT: usize = 250;
+    const PLANES: Planes = Planes::TriColor;
+    const DRIVER_OUTPUT_CONTROL: [u8; 3] = [0xf9, 0x00, 0x00];
+    const DATA_ENTRY_MODE: u8 = 0x03;
+    const BORDER_WAVEFORM: u8 = 0x05;
+    const DISPLAY_UPDATE_CONTROL_1: [u8; 2] = [0x00, 0x80];
+    const INITIALIZATION: &'static [(u8, &'static [u8])] =
+        &[(command::TEMPERATURE_SENSOR_CONTROL, &[0x80])];
+    const FULL_REFRESH: u8 = 0xf7;
+    const PARTIAL_REFRESH: u8 = 0xff;
+
+    #[cfg(feature = "draw-target")]
+    type Plane = [u8; 4000];
+
+    #[cfg(feature = "draw-target")]
+    const BLANK_PLANE: Self::Plane = [0xff; 4000];
+}
+
+/// WaveShare 2.9inches B v4 panel (128×296, black/white/red)
+#[derive(Debug)]
+pub struct Epd2in9bV4;
+
+impl Panel for Epd2in9bV4 {
+    const WIDTH: usize = 128;
+    const HEIGHT: usize = 296;
+    const PLANES: Planes = Planes::TriColor;
+    const DRIVER_OUTPUT_CONTROL: [u8; 3] = [0x27, 0x01, 0x00];
+    const DATA_ENTRY_MODE: u8 = 0x03;
+    const BORDER_WAVEFORM: u8 = 0x05;
+    const DISPLAY_UPDATE_CONTROL_1: [u8; 2] = [0x00, 0x80];
+    const INITIALIZATION: &'static [(u8, &'static [u8])] =
+        &[(command::TEMPERATURE_SENSOR_CONTROL, &[0x80])];
+    const FULL_REFRESH: u8 = 0xf7;
+    const PARTIAL_REFRESH: u8 = 0xff;
+
+    #[cfg(feature = "draw-target")]
+    type Plane = [u8; 4736];
+
+    #[cfg(feature = "draw-target")]
+    const BLANK_PLANE: Self::Plane = [0xff; 4736];
+}
+
+/// Check that a buffer has the same geometry as a panel
+///
+/// # Errors
+///
+/// Returns an error if the buffer has a different geometry
+#[cfg(feature = "draw-target")]
+pub(crate) fn check_buffer_size<
+    PANEL: Panel,
+    const WIDTH: usize,
+    const HEIGHT: usize,
+    const BYTE_SIZE: usize,
+>() -> Result<(), Error> {
+    if WIDTH == PANEL::WIDTH && HEIGHT == PANEL::HEIGHT && BYTE_SIZE == PANEL::BYTE_SIZE {
+        Ok(())
+    } else {
+        Err(Error::BufferSize)
+    }
+}
+
+/// Check that a strip has the same geometry as a panel
+///
+/// # Errors
+///
+/// Returns an error if the strip has a different geometry, or no rows
+#[cfg(feature = "draw-target")]
+pub(crate) fn check_strip_size<
+    PANEL: Panel,
+    const WIDTH: usize,
+    const HEIGHT: usize,
+    const ROWS: usize,
+    const BYTE_SIZE: usize,
+>() -> Result<(), Error> {
+    if WIDTH == PANEL::WIDTH
+        && HEIGHT == PANEL::HEIGHT
+        && ROWS > 0
+        && BYTE_SIZE == PANEL::LINE_WIDTH * ROWS
+    {
+        Ok(())
+    } else {
+        Err(Error::BufferSize)
+    }
+}
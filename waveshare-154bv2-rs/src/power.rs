@@ -0,0 +1,39 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Power states of a display
+
+use crate::Error;
+
+/// A power state of a display
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PowerState {
+    /// The display was not initialized yet
+    Uninitialized,
+
+    /// The display is initialized and accepts commands
+    Awake,
+
+    /// The display is in deep sleep, and must be woken up before drawing
+    DeepSleep,
+}
+
+impl PowerState {
+    /// Check that the display accepts drawing commands
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the display is not awake
+    pub(crate) fn ensure_awake(self) -> Result<(), Error> {
+        match self {
+            Self::Awake => Ok(()),
+            Self::Uninitialized => Err(Error::Uninitialized),
+            Self::DeepSleep => Err(Error::Asleep),
+        }
+    }
+}
@@ -0,0 +1,1068 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Display protocol independent of any I/O
+//!
+//! The protocol keeps track of the display state, and produces the sequences
+//! of [`Operation`]s needed to drive it.
+//! Blocking and async displays only execute the operations on their hardware.
+
+use core::iter::from_fn;
+use core::marker::PhantomData;
+
+use crate::command;
+use crate::config::Layout;
+use crate::config::RamWindow;
+use crate::refresh::BYPASS_CHROMATIC_RAM;
+use crate::refresh::FAST_REFRESH_TEMPERATURE;
+use crate::rle::compressed_lines;
+use crate::waveform::temperature_register;
+use crate::waveform::WaveformSource;
+use crate::CompressedImage;
+use crate::DisplayConfig;
+use crate::Error;
+use crate::Panel;
+use crate::Planes;
+use crate::PowerState;
+use crate::RefreshMode;
+
+#[cfg(feature = "draw-target")]
+use embedded_graphics_core::primitives::Rectangle;
+
+#[cfg(feature = "draw-target")]
+use crate::frame::planes_to_transfer;
+#[cfg(feature = "draw-target")]
+use crate::frame::Changes;
+#[cfg(feature = "draw-target")]
+use crate::frame::Generation;
+#[cfg(feature = "draw-target")]
+use crate::frame::Window;
+#[cfg(feature = "draw-target")]
+use crate::panel::check_buffer_size;
+#[cfg(feature = "draw-target")]
+use crate::panel::check_strip_size;
+#[cfg(feature = "draw-target")]
+use crate::Buffer;
+#[cfg(feature = "draw-target")]
+use crate::Frame;
+#[cfg(feature = "draw-target")]
+use crate::Strip;
+
+/// Maximal number of bytes stored in [`Parameters`]
+const MAX_PARAMETERS: usize = 4;
+
+/// An operation on the display hardware
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Operation<'data> {
+    /// Set the reset pin high or low
+    Reset(bool),
+
+    /// Wait for a number of milliseconds
+    Delay(u32),
+
+    /// Wait until the display is no longer busy
+    WaitUntilIdle,
+
+    /// Send a command
+    Command(u8),
+
+    /// Send the parameters of a command
+    Parameters(Parameters),
+
+    /// Send data
+    Data(&'data [u8]),
+
+    /// Send a byte repeated a number of times
+    Fill(u8, usize),
+
+    /// Send data transformed by an encoding
+    EncodedData(&'data [u8], Encoding),
+
+    /// Send run-length encoded data, transformed by an encoding after
+    /// decoding it
+    ///
+    /// Data with reversed bytes must contain a single line.
+    CompressedData(&'data [u8], Encoding),
+}
+
+/// A transformation of data before sending it
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Encoding {
+    /// Invert all bits
+    inverted: bool,
+
+    /// Reverse the order of bits in each byte
+    mirrored: bool,
+
+    /// Reverse the order of bytes
+    reversed: bool,
+}
+
+impl Encoding {
+    /// Split data in chunks, in the order they must be sent
+    pub(crate) fn chunks(self, data: &[u8], size: usize) -> impl Iterator<Item = &[u8]> {
+        directed(data.chunks(size), self.reversed)
+    }
+
+    /// Encode a chunk of data into a buffer of the same size
+    pub(crate) fn encode(self, chunk: &[u8], buffer: &mut [u8]) {
+        for (byte, datum) in buffer.iter_mut().zip(chunk) {
+            let datum = if self.mirrored {
+                datum.reverse_bits()
+            } else {
+                *datum
+            };
+            *byte = if self.inverted { !datum } else { datum };
+        }
+        if self.reversed {
+            buffer.reverse();
+        }
+    }
+
+    /// Check whether data is sent unchanged
+    fn is_identity(self) -> bool {
+        self == Self::default()
+    }
+}
+
+/// A few bytes of data sent after a command
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Parameters {
+    /// Storage for bytes
+    bytes: [u8; MAX_PARAMETERS],
+
+    /// Number of bytes in use
+    length: usize,
+}
+
+impl Parameters {
+    /// Store parameters
+    ///
+    /// Bytes exceeding [`MAX_PARAMETERS`] are ignored.
+    fn new(data: &[u8]) -> Self {
+        let mut bytes = [0x00; MAX_PARAMETERS];
+        for (byte, datum) in bytes.iter_mut().zip(data) {
+            *byte = *datum;
+        }
+        Self {
+            bytes,
+            length: data.len().min(MAX_PARAMETERS),
+        }
+    }
+
+    /// Get the stored bytes
+    pub(crate) fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.length]
+    }
+}
+
+/// State of a display and generator of its operations
+///
+/// Methods producing operations also update the state as if they were
+/// executed.
+/// State that must only change after the operations succeeded is updated by
+/// the corresponding `complete_*()` methods.
+pub(crate) struct Protocol<PANEL: Panel> {
+    /// Panel description
+    panel: PhantomData<PANEL>,
+
+    /// Power state
+    state: PowerState,
+
+    /// Source of the waveform used for refreshing
+    waveform: WaveformSource,
+
+    /// Controller configuration resolved for the panel
+    layout: Layout,
+
+    /// Mode of a refresh started but not yet finished
+    pending_refresh: Option<RefreshMode>,
+
+    #[cfg(feature = "draw-target")]
+    /// Generation of the display content, compared against frames
+    generation: Generation,
+}
+
+impl<PANEL: Panel> Protocol<PANEL> {
+    /// Create the protocol for an uninitialized display
+    pub(crate) fn new() -> Self {
+        Self {
+            panel: PhantomData,
+            state: PowerState::Uninitialized,
+            waveform: WaveformSource::Builtin,
+            layout: DisplayConfig::new().layout::<PANEL>(),
+            pending_refresh: None,
+            #[cfg(feature = "draw-target")]
+            generation: Generation::default(),
+        }
+    }
+
+    /// Set the controller configuration applied by [`Self::initialize()`]
+    pub(crate) fn set_config(&mut self, config: DisplayConfig) {
+        self.layout = config.layout::<PANEL>();
+    }
+
+    /// Return the current power state
+    pub(crate) fn power_state(&self) -> PowerState {
+        self.state
+    }
+
+    /// Produce operations to reset and initialize the display
+    ///
+    /// The display is considered uninitialized until
+    /// [`Self::complete_initialize()`] is called.
+    pub(crate) fn initialize(&mut self) -> impl Iterator<Item = Operation<'static>> {
+        self.state = PowerState::Uninitialized;
+        self.waveform = WaveformSource::Builtin;
+        self.pending_refresh = None;
+        #[cfg(feature = "draw-target")]
+        {
+            self.generation.reset();
+        }
+
+        let initialization = PANEL::INITIALIZATION
+            .iter()
+            .flat_map(|&(command, data)| [Operation::Command(command), Operation::Data(data)]);
+
+        let layout = self.layout;
+        let window = layout.full_ram_window();
+
+        hardware_reset()
+            .into_iter()
+            .chain([
+                Operation::WaitUntilIdle,
+                Operation::Command(command::SOFTWARE_RESET),
+                Operation::WaitUntilIdle,
+            ])
+            .chain(command(
+                command::DRIVER_OUTPUT_CONTROL,
+                &layout.driver_output_control,
+            ))
+            .chain(command(command::DATA_ENTRY_MODE, &[layout.data_entry_mode]))
+            .chain(set_ram_window(window))
+            .chain(command(
+                command::BORDER_WAVEFORM_CONTROL,
+                &[layout.border_waveform],
+            ))
+            .chain(display_update_control_1::<PANEL>())
+            .chain(initialization)
+            .chain(set_ram_address_counters(window))
+            .chain([Operation::WaitUntilIdle])
+    }
+
+    /// Mark the display as initialized
+    pub(crate) fn complete_initialize(&mut self) {
+        self.state = PowerState::Awake;
+    }
+
+    /// Produce operations to put the display in deep sleep
+    ///
+    /// Return `None` if the display is already in deep sleep.
+    pub(crate) fn sleep(&self) -> Option<[Operation<'static>; 3]> {
+        let [command, parameters] = command(command::DEEP_SLEEP_MODE, &[0x01]);
+        (self.state != PowerState::DeepSleep).then_some([
+            command,
+            parameters,
+            Operation::Delay(200),
+        ])
+    }
+
+    /// Mark the display as in deep sleep
+    pub(crate) fn complete_sleep(&mut self) {
+        self.state = PowerState::DeepSleep;
+    }
+
+    /// Produce operations to write a temperature to the temperature register
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the display is not awake
+    pub(crate) fn set_temperature(&self, celsius: f32) -> Result<[Operation<'static>; 2], Error> {
+        self.state.ensure_awake()?;
+        Ok(command(
+            command::WRITE_TEMPERATURE_REGISTER,
+            &temperature_register(celsius),
+        ))
+    }
+
+    /// Use a written temperature for selecting the waveform
+    ///
+    /// The temperature is ignored when a custom LUT is loaded.
+    pub(crate) fn complete_set_temperature(&mut self, celsius: f32) {
+        if self.waveform != WaveformSource::CustomLut {
+            self.waveform = WaveformSource::ExternalTemperature(temperature_register(celsius));
+        }
+    }
+
+    /// Produce operations to load a custom waveform LUT
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the display is not awake
+    pub(crate) fn load_lut<'data>(&self, lut: &'data [u8]) -> Result<[Operation<'data>; 2], Error> {
+        self.state.ensure_awake()?;
+        Ok([
+            Operation::Command(command::WRITE_LUT_REGISTER),
+            Operation::Data(lut),
+        ])
+    }
+
+    /// Use the loaded custom LUT for all following refreshes
+    pub(crate) fn complete_load_lut(&mut self) {
+        self.waveform = WaveformSource::CustomLut;
+    }
+
+    /// Produce operations to start refreshing the display with the content
+    /// of its RAM
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the display is not awake
+    pub(crate) fn start_refresh(
+        &self,
+        mode: RefreshMode,
+    ) -> Result<impl Iterator<Item = Operation<'static>>, Error> {
+        self.state.ensure_awake()?;
+        Ok(self.refresh(mode))
+    }
+
+    /// Remember that a refresh was started and must be finished
+    ///
+    /// All operations producing a refresh only start it, and the refresh is
+    /// finished by the operations of [`Self::finish_refresh()`].
+    pub(crate) fn complete_start_refresh(&mut self, mode: RefreshMode) {
+        self.pending_refresh = Some(mode);
+    }
+
+    /// Produce operations to wait for a started refresh to complete
+    ///
+    /// Return `None` if no refresh was started.
+    pub(crate) fn finish_refresh(&mut self) -> Option<impl Iterator<Item = Operation<'static>>> {
+        self.pending_refresh
+            .take()
+            .map(|mode| finish_refresh_sequence::<PANEL>(mode, self.waveform))
+    }
+
+    /// Produce operations to clear and refresh the display
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the display is not awake
+    pub(crate) fn clear(&mut self) -> Result<impl Iterator<Item = Operation<'static>>, Error> {
+        self.state.ensure_awake()?;
+        #[cfg(feature = "draw-target")]
+        {
+            self.generation.write();
+        }
+
+        let chromatic = (PANEL::PLANES == Planes::TriColor).then_some([
+            Operation::Command(command::WRITE_RAM_CHROMATIC),
+            Operation::Fill(0x00, PANEL::BYTE_SIZE),
+        ]);
+
+        Ok([
+            Operation::Command(command::WRITE_RAM_BLACK),
+            Operation::Fill(0xff, PANEL::BYTE_SIZE),
+        ]
+        .into_iter()
+        .chain(chromatic.into_iter().flatten())
+        .chain(self.refresh(RefreshMode::Full)))
+    }
+
+    #[cfg(feature = "draw-target")]
+    /// Check whether the display already shows the content of a buffer,
+    /// according to the last frame sent to it
+    pub(crate) fn is_up_to_date<const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize>(
+        &self,
+        buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+        frame: &Frame<PANEL>,
+        mode: RefreshMode,
+    ) -> bool {
+        let (black, chromatic) =
+            planes_to_transfer::<PANEL, WIDTH, HEIGHT, BYTE_SIZE>(buffer, mode);
+        check_buffer_size::<PANEL, WIDTH, HEIGHT, BYTE_SIZE>().is_ok()
+            && frame.changes(black, chromatic, self.generation) == Changes::None
+    }
+
+    #[cfg(feature = "draw-target")]
+    /// Produce operations to update the display with the content of a buffer
+    ///
+    /// Without the last frame sent to the display, whole planes are
+    /// transferred.
+    /// Otherwise, return `None` if the display already shows the buffer.
+    /// The buffer is remembered as the last frame only after
+    /// [`Self::complete_draw_buffer()`] is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the display is not awake, or if the buffer does not
+    /// match the panel
+    pub(crate) fn draw_buffer<
+        'data,
+        const WIDTH: usize,
+        const HEIGHT: usize,
+        const BYTE_SIZE: usize,
+    >(
+        &mut self,
+        buffer: &'data Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+        frame: Option<&Frame<PANEL>>,
+        mode: RefreshMode,
+    ) -> Result<Option<impl Iterator<Item = Operation<'data>>>, Error> {
+        self.state.ensure_awake()?;
+        check_buffer_size::<PANEL, WIDTH, HEIGHT, BYTE_SIZE>()?;
+
+        let (black, chromatic) =
+            planes_to_transfer::<PANEL, WIDTH, HEIGHT, BYTE_SIZE>(buffer, mode);
+        let changes = frame.map_or(Changes::All, |frame| {
+            frame.changes(black, chromatic, self.generation)
+        });
+
+        let windows = match changes {
+            Changes::None => return Ok(None),
+            Changes::All => None,
+            Changes::Windows {
+                black: black_window,
+                chromatic: chromatic_window,
+            } => Some((black_window, chromatic_window)),
+        };
+
+        self.generation.write();
+
+        let layout = self.layout;
+        let whole_planes = windows.is_none().then(|| {
+            write_black(layout, black).chain(
+                chromatic
+                    .map(|chromatic| write_chromatic(layout, chromatic))
+                    .into_iter()
+                    .flatten(),
+            )
+        });
+        let dirty_windows = windows.map(|(black_window, chromatic_window)| {
+            black_window
+                .map(|window| write_window::<PANEL>(layout, window, Some(black), None))
+                .into_iter()
+                .flatten()
+                .chain(
+                    chromatic_window
+                        .map(|window| write_window::<PANEL>(layout, window, None, chromatic))
+                        .into_iter()
+                        .flatten(),
+                )
+                .chain(restore_ram_window(layout))
+        });
+
+        Ok(Some(
+            whole_planes
+                .into_iter()
+                .flatten()
+                .chain(dirty_windows.into_iter().flatten())
+                .chain(self.refresh(mode)),
+        ))
+    }
+
+    #[cfg(feature = "draw-target")]
+    /// Remember a buffer as the last frame after it was drawn
+    pub(crate) fn complete_draw_buffer<
+        const WIDTH: usize,
+        const HEIGHT: usize,
+        const BYTE_SIZE: usize,
+    >(
+        &self,
+        buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+        frame: &mut Frame<PANEL>,
+        mode: RefreshMode,
+    ) {
+        let (black, chromatic) =
+            planes_to_transfer::<PANEL, WIDTH, HEIGHT, BYTE_SIZE>(buffer, mode);
+        frame.store(black, chromatic, self.generation);
+    }
+
+    #[cfg(feature = "draw-target")]
+    /// Produce operations to update a rectangular area of the display
+    ///
+    /// Return `None` if the area is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the display is not awake, or if the buffer does not
+    /// match the panel
+    pub(crate) fn draw_buffer_window<
+        'data,
+        const WIDTH: usize,
+        const HEIGHT: usize,
+        const BYTE_SIZE: usize,
+    >(
+        &mut self,
+        buffer: &'data Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+        area: &Rectangle,
+        mode: RefreshMode,
+    ) -> Result<Option<impl Iterator<Item = Operation<'data>>>, Error> {
+        self.state.ensure_awake()?;
+        check_buffer_size::<PANEL, WIDTH, HEIGHT, BYTE_SIZE>()?;
+
+        let Some(window) = buffer_window(buffer, area) else {
+            return Ok(None);
+        };
+        let (black, chromatic) =
+            planes_to_transfer::<PANEL, WIDTH, HEIGHT, BYTE_SIZE>(buffer, mode);
+
+        self.generation.write();
+
+        Ok(Some(
+            write_window::<PANEL>(self.layout, window, Some(black), chromatic)
+                .chain(restore_ram_window(self.layout))
+                .chain(self.refresh(mode)),
+        ))
+    }
+
+    #[cfg(feature = "draw-target")]
+    /// Prepare for updating the display strip by strip
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the display is not awake, or if the strip does not
+    /// match the panel
+    pub(crate) fn draw_strips<
+        const WIDTH: usize,
+        const HEIGHT: usize,
+        const ROWS: usize,
+        const BYTE_SIZE: usize,
+    >(
+        &mut self,
+    ) -> Result<(), Error> {
+        self.state.ensure_awake()?;
+        check_strip_size::<PANEL, WIDTH, HEIGHT, ROWS, BYTE_SIZE>()?;
+        self.generation.write();
+        Ok(())
+    }
+
+    #[cfg(feature = "draw-target")]
+    /// Produce operations to write a strip to the corresponding rows of RAM
+    pub(crate) fn transfer_strip<
+        'data,
+        const WIDTH: usize,
+        const HEIGHT: usize,
+        const ROWS: usize,
+        const BYTE_SIZE: usize,
+    >(
+        &self,
+        strip: &'data Strip<WIDTH, HEIGHT, ROWS, BYTE_SIZE>,
+        mode: RefreshMode,
+    ) -> impl Iterator<Item = Operation<'data>> {
+        let layout = self.layout;
+        let first_row = strip.first_row();
+        let rows = strip.rows();
+        let length = rows * PANEL::LINE_WIDTH;
+        let window = layout.ram_window(0, PANEL::LINE_WIDTH - 1, first_row, first_row + rows - 1);
+
+        let chromatic =
+            (PANEL::PLANES == Planes::TriColor && mode != RefreshMode::Fast).then(|| {
+                set_ram_address_counters(window)
+                    .chain(write_chromatic(layout, &strip.chromatic_buffer()[..length]))
+            });
+
+        set_ram_window(window)
+            .chain(set_ram_address_counters(window))
+            .chain(write_black(layout, &strip.black_buffer()[..length]))
+            .chain(chromatic.into_iter().flatten())
+    }
+
+    #[cfg(feature = "draw-target")]
+    /// Produce operations to refresh the display after writing all strips
+    pub(crate) fn refresh_strips(
+        &self,
+        mode: RefreshMode,
+    ) -> impl Iterator<Item = Operation<'static>> {
+        restore_ram_window(self.layout).chain(self.refresh(mode))
+    }
+
+    /// Produce operations to update the display with raw color planes
+    ///
+    /// With [`RefreshMode::Fast`] the chromatic plane is not transferred.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the display is not awake
+    pub(crate) fn transfer_channels<'data>(
+        &mut self,
+        black: Option<&'data [u8]>,
+        chromatic: Option<&'data [u8]>,
+        mode: RefreshMode,
+    ) -> Result<impl Iterator<Item = Operation<'data>>, Error> {
+        self.state.ensure_awake()?;
+
+        let chromatic = chromatic.filter(|_| mode != RefreshMode::Fast);
+        #[cfg(feature = "draw-target")]
+        if black.is_some() || chromatic.is_some() {
+            self.generation.write();
+        }
+
+        let layout = self.layout;
+        Ok(black
+            .map(|black| write_black(layout, black))
+            .into_iter()
+            .flatten()
+            .chain(
+                chromatic
+                    .map(|chromatic| write_chromatic(layout, chromatic))
+                    .into_iter()
+                    .flatten(),
+            )
+            .chain(self.refresh(mode)))
+    }
+
+    /// Produce operations to write the black plane
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the display is not awake
+    pub(crate) fn transfer_black<'data>(
+        &mut self,
+        black: &'data [u8],
+    ) -> Result<impl Iterator<Item = Operation<'data>>, Error> {
+        self.state.ensure_awake()?;
+        #[cfg(feature = "draw-target")]
+        {
+            self.generation.write();
+        }
+        Ok(write_black(self.layout, black))
+    }
+
+    /// Produce operations to write the chromatic plane
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the display is not awake
+    pub(crate) fn transfer_chromatic<'data>(
+        &mut self,
+        chromatic: &'data [u8],
+    ) -> Result<impl Iterator<Item = Operation<'data>>, Error> {
+        self.state.ensure_awake()?;
+        #[cfg(feature = "draw-target")]
+        {
+            self.generation.write();
+        }
+        Ok(write_chromatic(self.layout, chromatic))
+    }
+
+    /// Produce operations to update the display with a compressed image
+    ///
+    /// The chromatic plane is only transferred to tri-color panels, and not
+    /// with [`RefreshMode::Fast`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the display is not awake, or if the image is
+    /// malformed or does not match the panel
+    pub(crate) fn draw_compressed<'data>(
+        &mut self,
+        image: &CompressedImage<'data>,
+        mode: RefreshMode,
+    ) -> Result<impl Iterator<Item = Operation<'data>>, Error> {
+        self.state.ensure_awake()?;
+
+        let (black, chromatic) = image.planes::<PANEL>()?;
+        let chromatic =
+            (PANEL::PLANES == Planes::TriColor && mode != RefreshMode::Fast).then_some(chromatic);
+        #[cfg(feature = "draw-target")]
+        {
+            self.generation.write();
+        }
+
+        let layout = self.layout;
+        Ok(
+            write_compressed(layout, command::WRITE_RAM_BLACK, black, false)
+                .chain(
+                    chromatic
+                        .map(|chromatic| {
+                            write_compressed(layout, command::WRITE_RAM_CHROMATIC, chromatic, true)
+                        })
+                        .into_iter()
+                        .flatten(),
+                )
+                .chain(self.refresh(mode)),
+        )
+    }
+
+    /// Produce operations to start a refresh
+    fn refresh<'data>(&self, mode: RefreshMode) -> impl Iterator<Item = Operation<'data>> {
+        start_refresh_sequence::<PANEL>(mode, self.waveform)
+    }
+}
+
+/// Produce operations for a command with parameters
+fn command<'data>(command: u8, parameters: &[u8]) -> [Operation<'data>; 2] {
+    [
+        Operation::Command(command),
+        Operation::Parameters(Parameters::new(parameters)),
+    ]
+}
+
+/// Produce operations to reset the display through its reset pin
+fn hardware_reset<'data>() -> [Operation<'data>; 6] {
+    [
+        Operation::Reset(true),
+        Operation::Delay(10),
+        Operation::Reset(false),
+        Operation::Delay(10),
+        Operation::Reset(true),
+        Operation::Delay(200),
+    ]
+}
+
+/// Produce operations to set display update control 1 to the panel default
+fn display_update_control_1<'data, PANEL: Panel>() -> [Operation<'data>; 2] {
+    command(
+        command::DISPLAY_UPDATE_CONTROL_1,
+        &PANEL::DISPLAY_UPDATE_CONTROL_1,
+    )
+}
+
+/// Produce operations to set RAM address counters to the start of a window
+fn set_ram_address_counters<'data>(window: RamWindow) -> impl Iterator<Item = Operation<'data>> {
+    command(command::SET_RAM_X_ADDRESS_COUNTER, &[window.x_start])
+        .into_iter()
+        .chain(command(
+            command::SET_RAM_Y_ADDRESS_COUNTER,
+            &window.y_start.to_le_bytes(),
+        ))
+}
+
+/// Produce operations to set RAM window
+fn set_ram_window<'data>(window: RamWindow) -> impl Iterator<Item = Operation<'data>> {
+    let [y_start_0, y_start_1] = window.y_start.to_le_bytes();
+    let [y_end_0, y_end_1] = window.y_end.to_le_bytes();
+
+    command(
+        command::SET_RAM_X_ADDRESS_START_END_POSITION,
+        &[window.x_start, window.x_end],
+    )
+    .into_iter()
+    .chain(command(
+        command::SET_RAM_Y_ADDRESS_START_END_POSITION,
+        &[y_start_0, y_start_1, y_end_0, y_end_1],
+    ))
+}
+
+#[cfg(feature = "draw-target")]
+/// Produce operations to restore the full RAM window and reset address
+/// counters
+fn restore_ram_window<'data>(layout: Layout) -> impl Iterator<Item = Operation<'data>> {
+    let window = layout.full_ram_window();
+    set_ram_window(window).chain(set_ram_address_counters(window))
+}
+
+/// Produce operations to write the black plane at the current RAM address
+fn write_black(layout: Layout, black: &[u8]) -> impl Iterator<Item = Operation<'_>> {
+    [Operation::Command(command::WRITE_RAM_BLACK)]
+        .into_iter()
+        .chain(plane_lines(layout, black, false))
+}
+
+/// Produce operations to write the chromatic plane at the current RAM address
+///
+/// Chromatic RAM stores chromatic pixels as set bits, so the plane is
+/// inverted.
+fn write_chromatic(layout: Layout, chromatic: &[u8]) -> impl Iterator<Item = Operation<'_>> {
+    [Operation::Command(command::WRITE_RAM_CHROMATIC)]
+        .into_iter()
+        .chain(plane_lines(layout, chromatic, true))
+}
+
+/// Produce operations to send whole lines of a plane
+///
+/// Lines are sent one by one only when the layout reorders them.
+fn plane_lines(
+    layout: Layout,
+    plane: &[u8],
+    inverted: bool,
+) -> impl Iterator<Item = Operation<'_>> {
+    let size = if layout.reversed_lines() || layout.reversed_rows() {
+        layout.line_width()
+    } else {
+        plane.len()
+    };
+
+    directed(plane.chunks(size.max(1)), layout.reversed_rows())
+        .map(move |lines| encoded_data(layout, lines, inverted))
+}
+
+/// Produce an operation to send lines of a plane
+///
+/// Data that does not need any encoding is sent unchanged.
+fn encoded_data(layout: Layout, data: &[u8], inverted: bool) -> Operation<'_> {
+    let encoding = line_encoding(layout, inverted);
+    if encoding.is_identity() {
+        Operation::Data(data)
+    } else {
+        Operation::EncodedData(data, encoding)
+    }
+}
+
+/// Produce operations to write a compressed plane at the current RAM address
+///
+/// Compressed lines can only be sent in order, so if rows must be sent in
+/// reverse order the RAM address counters are set before each line, and
+/// reset at the end.
+fn write_compressed(
+    layout: Layout,
+    command: u8,
+    plane: &[u8],
+    inverted: bool,
+) -> impl Iterator<Item = Operation<'_>> {
+    let encoding = line_encoding(layout, inverted);
+    let line_width = layout.line_width();
+    let addressed = layout.reversed_rows();
+    let split = layout.reversed_lines() || addressed;
+
+    let lines = split
+        .then(|| compressed_lines(plane, line_width))
+        .into_iter()
+        .flatten()
+        .chain((!split).then_some(plane))
+        .enumerate()
+        .flat_map(move |(row, line)| {
+            addressed
+                .then(|| {
+                    set_ram_address_counters(layout.ram_window(0, line_width - 1, row, row))
+                        .chain([Operation::Command(command)])
+                })
+                .into_iter()
+                .flatten()
+                .chain([Operation::CompressedData(line, encoding)])
+        });
+
+    [Operation::Command(command)]
+        .into_iter()
+        .filter(move |_| !addressed)
+        .chain(lines)
+        .chain(
+            addressed
+                .then(|| set_ram_address_counters(layout.full_ram_window()))
+                .into_iter()
+                .flatten(),
+        )
+}
+
+/// Get the encoding of lines of a plane
+fn line_encoding(layout: Layout, inverted: bool) -> Encoding {
+    Encoding {
+        inverted,
+        mirrored: layout.mirrored_bits(),
+        reversed: layout.reversed_lines(),
+    }
+}
+
+/// Iterate forwards, or backwards if reversed
+fn directed<ITERATOR: DoubleEndedIterator>(
+    mut iterator: ITERATOR,
+    reversed: bool,
+) -> impl Iterator<Item = ITERATOR::Item> {
+    from_fn(move || {
+        if reversed {
+            iterator.next_back()
+        } else {
+            iterator.next()
+        }
+    })
+}
+
+#[cfg(feature = "draw-target")]
+/// Convert an area in the buffer's rotated coordinates to a window
+///
+/// Return `None` if the area is empty.
+#[allow(clippy::cast_sign_loss)]
+fn buffer_window<const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize>(
+    buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+    area: &Rectangle,
+) -> Option<Window> {
+    let area = buffer.physical_area(area);
+    let bottom_right = area.bottom_right()?;
+
+    Some(Window {
+        first_column: area.top_left.x as usize / 8,
+        last_column: bottom_right.x as usize / 8,
+        first_row: area.top_left.y as usize,
+        last_row: bottom_right.y as usize,
+    })
+}
+
+#[cfg(feature = "draw-target")]
+/// Produce operations to write an area of color planes to display RAM
+///
+/// The RAM window is left set to the area.
+fn write_window<'data, PANEL: Panel>(
+    layout: Layout,
+    window: Window,
+    black: Option<&'data [u8]>,
+    chromatic: Option<&'data [u8]>,
+) -> impl Iterator<Item = Operation<'data>> {
+    let ram_window = layout.ram_window(
+        window.first_column,
+        window.last_column,
+        window.first_row,
+        window.last_row,
+    );
+
+    let black = black.map(|black| {
+        set_ram_address_counters(ram_window)
+            .chain([Operation::Command(command::WRITE_RAM_BLACK)])
+            .chain(
+                window_lines::<PANEL>(layout, window, black)
+                    .map(move |line| encoded_data(layout, line, false)),
+            )
+    });
+    let chromatic = chromatic.map(|chromatic| {
+        set_ram_address_counters(ram_window)
+            .chain([Operation::Command(command::WRITE_RAM_CHROMATIC)])
+            .chain(
+                window_lines::<PANEL>(layout, window, chromatic)
+                    .map(move |line| encoded_data(layout, line, true)),
+            )
+    });
+
+    set_ram_window(ram_window)
+        .chain(black.into_iter().flatten())
+        .chain(chromatic.into_iter().flatten())
+}
+
+#[cfg(feature = "draw-target")]
+/// Iterate over the lines of a plane falling in a window
+///
+/// Lines are in the order they must be sent.
+fn window_lines<PANEL: Panel>(
+    layout: Layout,
+    window: Window,
+    plane: &[u8],
+) -> impl Iterator<Item = &[u8]> {
+    directed(window.first_row..=window.last_row, layout.reversed_rows()).map(move |row| {
+        let offset = row * PANEL::LINE_WIDTH;
+        &plane[offset + window.first_column..=offset + window.last_column]
+    })
+}
+
+/// Produce operations to start refreshing the display
+///
+/// For [`RefreshMode::Fast`], chromatic RAM is bypassed on tri-color panels,
+/// and unless a custom LUT is loaded, a high temperature is written to select
+/// the fastest waveform.
+fn start_refresh_sequence<'data, PANEL: Panel>(
+    mode: RefreshMode,
+    waveform: WaveformSource,
+) -> impl Iterator<Item = Operation<'data>> {
+    let fast = mode == RefreshMode::Fast;
+    let bypass_chromatic = fast && PANEL::PLANES == Planes::TriColor;
+    let fast_temperature = (fast && waveform != WaveformSource::CustomLut)
+        .then(|| temperature_register(FAST_REFRESH_TEMPERATURE));
+
+    let sequence = match mode {
+        RefreshMode::Full => waveform.update_sequence(PANEL::FULL_REFRESH),
+        RefreshMode::Fast => fast_temperature
+            .map_or(waveform, WaveformSource::ExternalTemperature)
+            .update_sequence(PANEL::FULL_REFRESH),
+        RefreshMode::Partial => waveform.update_sequence(PANEL::PARTIAL_REFRESH),
+    };
+
+    let [first, second] = PANEL::DISPLAY_UPDATE_CONTROL_1;
+    let bypass = bypass_chromatic.then(|| {
+        command(
+            command::DISPLAY_UPDATE_CONTROL_1,
+            &[first | BYPASS_CHROMATIC_RAM, second],
+        )
+    });
+
+    bypass
+        .into_iter()
+        .flatten()
+        .chain(
+            fast_temperature
+                .map(write_temperature)
+                .into_iter()
+                .flatten(),
+        )
+        .chain(command(command::DISPLAY_UPDATE_CONTROL_2, &[sequence]))
+        .chain([Operation::Command(command::MASTER_ACTIVATION)])
+}
+
+/// Produce operations to wait for a refresh to complete
+///
+/// Display settings changed by [`start_refresh_sequence()`] are restored.
+fn finish_refresh_sequence<'data, PANEL: Panel>(
+    mode: RefreshMode,
+    waveform: WaveformSource,
+) -> impl Iterator<Item = Operation<'data>> {
+    let fast = mode == RefreshMode::Fast;
+    let bypass_chromatic = fast && PANEL::PLANES == Planes::TriColor;
+    let restored_temperature = match waveform {
+        WaveformSource::ExternalTemperature(temperature) if fast => Some(temperature),
+        WaveformSource::Builtin
+        | WaveformSource::ExternalTemperature(_)
+        | WaveformSource::CustomLut => None,
+    };
+
+    [Operation::WaitUntilIdle]
+        .into_iter()
+        .chain(
+            bypass_chromatic
+                .then(display_update_control_1::<PANEL>)
+                .into_iter()
+                .flatten(),
+        )
+        .chain(
+            restored_temperature
+                .map(write_temperature)
+                .into_iter()
+                .flatten(),
+        )
+}
+
+/// Produce operations to write the temperature register
+fn write_temperature<'data>(temperature: [u8; 2]) -> [Operation<'data>; 2] {
+    command(command::WRITE_TEMPERATURE_REGISTER, &temperature)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    use crate::Epd1in54V2;
+    use crate::Epd1in54bV2;
+
+    #[test]
+    fn clear_operations() {
+        let mut protocol = Protocol::<Epd1in54bV2>::new();
+        protocol.complete_initialize();
+
+        // Each plane is filled by a single operation
+        assert_eq!(
+            protocol.clear().map(Iterator::collect::<Vec<_>>),
+            Ok(vec![
+                Operation::Command(0x24),
+                Operation::Fill(0xff, 5000),
+                Operation::Command(0x26),
+                Operation::Fill(0x00, 5000),
+                Operation::Command(0x22),
+                Operation::Parameters(Parameters::new(&[0xf7])),
+                Operation::Command(0x20),
+            ])
+        );
+
+        let mut protocol = Protocol::<Epd1in54V2>::new();
+        protocol.complete_initialize();
+        assert_eq!(
+            protocol.clear().map(Iterator::count),
+            Ok(5),
+            "Black/white panels have no chromatic plane"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Refresh modes
+
+/// Display update control 1 flag for bypassing chromatic RAM content as 0
+pub(crate) const BYPASS_CHROMATIC_RAM: u8 = 0b0100_0000;
+
+/// Temperature written to the display to select its fastest waveform
+pub(crate) const FAST_REFRESH_TEMPERATURE: f32 = 100.0;
+
+/// A mode for refreshing the display
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RefreshMode {
+    /// Full refresh of all color planes
+    ///
+    /// This is the slowest mode, taking around 15 seconds on tri-color
+    /// displays, but it leaves no ghosting.
+    Full,
+
+    /// Fast black/white refresh
+    ///
+    /// The chromatic plane is ignored and the display is driven with its
+    /// fastest waveform.
+    /// Chromatic pixels are shown as white, and some ghosting may remain
+    /// until the next full refresh.
+    Fast,
+
+    /// Partial refresh
+    ///
+    /// Only pixels that changed are driven, without flashing the display.
+    Partial,
+}
@@ -0,0 +1,376 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Run-length encoded images
+//!
+//! A compressed image starts with the panel width and height in pixels, as
+//! little-endian 16 bits integers.
+//! They are followed by the black plane and by the chromatic plane of a
+//! [`Buffer`](crate::Buffer), both compressed with `PackBits`.
+//!
+//! Each packet starts with a header byte `n`:
+//!
+//! * `0..=127`: the next `n + 1` bytes are copied literally;
+//! * `129..=255`: the next byte is repeated `257 - n` times;
+//! * `128`: nothing.
+//!
+//! Every line of a plane is compressed on its own, so packets never cross
+//! lines, and lines can be located without decoding them.
+
+use core::iter::from_fn;
+
+#[cfg(all(feature = "std", feature = "draw-target"))]
+extern crate alloc;
+
+#[cfg(all(feature = "std", feature = "draw-target"))]
+use alloc::vec::Vec;
+
+use crate::Error;
+use crate::Panel;
+
+#[cfg(all(feature = "std", feature = "draw-target"))]
+use crate::Buffer;
+
+/// Size of the header in bytes
+const HEADER_SIZE: usize = 4;
+
+/// Maximal number of bytes in a packet
+#[cfg(all(feature = "std", feature = "draw-target"))]
+const MAX_PACKET_LENGTH: usize = 128;
+
+/// An image compressed with run-length encoding
+///
+/// Images are usually created on the host with [`compress_buffer()`], and
+/// included in the firmware with [`include_bytes!()`].
+/// They are checked against the panel when drawn.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CompressedImage<'data> {
+    /// Compressed data, including the header
+    data: &'data [u8],
+}
+
+impl<'data> CompressedImage<'data> {
+    /// Create a compressed image from its data
+    #[must_use]
+    pub const fn new(data: &'data [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Get the compressed data, including the header
+    #[must_use]
+    pub const fn as_bytes(&self) -> &'data [u8] {
+        self.data
+    }
+
+    /// Split the compressed planes of a panel
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image does not match the panel, or if any of
+    /// its planes is malformed
+    pub(crate) fn planes<PANEL: Panel>(&self) -> Result<(&'data [u8], &'data [u8]), Error> {
+        if self.data.len() < HEADER_SIZE {
+            return Err(Error::InvalidImage);
+        }
+        let (header, data) = self.data.split_at(HEADER_SIZE);
+        let width = u16::from_le_bytes([header[0], header[1]]);
+        let height = u16::from_le_bytes([header[2], header[3]]);
+        if usize::from(width) != PANEL::WIDTH || usize::from(height) != PANEL::HEIGHT {
+            return Err(Error::InvalidImage);
+        }
+
+        let (black, data) = split_plane(data, PANEL::LINE_WIDTH, PANEL::HEIGHT)?;
+        let (chromatic, data) = split_plane(data, PANEL::LINE_WIDTH, PANEL::HEIGHT)?;
+        if !data.is_empty() {
+            return Err(Error::InvalidImage);
+        }
+
+        Ok((black, chromatic))
+    }
+}
+
+/// A decoder of compressed data
+pub(crate) struct Decoder<'data> {
+    /// Compressed data following the current packet header
+    data: &'data [u8],
+
+    /// Number of bytes left in the current packet
+    remaining: usize,
+
+    /// Flag for a packet repeating a single byte
+    repeated: bool,
+}
+
+impl<'data> Decoder<'data> {
+    /// Create a decoder
+    pub(crate) fn new(data: &'data [u8]) -> Self {
+        Self {
+            data,
+            remaining: 0,
+            repeated: false,
+        }
+    }
+
+    /// Decode bytes into a buffer
+    ///
+    /// Return the number of decoded bytes, which is smaller than the buffer
+    /// only at the end of data.
+    pub(crate) fn read(&mut self, buffer: &mut [u8]) -> usize {
+        let mut length = 0;
+        for (byte, decoded) in buffer.iter_mut().zip(self) {
+            *byte = decoded;
+            length += 1;
+        }
+        length
+    }
+}
+
+impl Iterator for Decoder<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining == 0 {
+            let (&header, data) = self.data.split_first()?;
+            self.data = data;
+            (self.remaining, self.repeated) = packet_length(header);
+        }
+
+        let &byte = self.data.first()?;
+        self.remaining -= 1;
+        if !self.repeated || self.remaining == 0 {
+            self.data = self.data.get(1..).unwrap_or_default();
+        }
+
+        Some(byte)
+    }
+}
+
+/// Split compressed data in lines
+///
+/// Iteration stops at the first malformed line.
+pub(crate) fn compressed_lines(mut data: &[u8], line_width: usize) -> impl Iterator<Item = &[u8]> {
+    from_fn(move || {
+        let size = compressed_line_size(data, line_width).ok()?;
+        let (line, rest) = data.split_at(size);
+        data = rest;
+        Some(line)
+    })
+}
+
+/// Split a compressed plane from the data following it
+///
+/// # Errors
+///
+/// Returns an error if the plane is malformed
+fn split_plane(data: &[u8], line_width: usize, height: usize) -> Result<(&[u8], &[u8]), Error> {
+    let mut size = 0;
+    for _ in 0..height {
+        let rest = data.get(size..).unwrap_or_default();
+        size += compressed_line_size(rest, line_width)?;
+    }
+
+    Ok(data.split_at(size))
+}
+
+/// Get the size of the first compressed line
+///
+/// # Errors
+///
+/// Returns an error if the line is truncated, or if its last packet extends
+/// past its end
+fn compressed_line_size(data: &[u8], line_width: usize) -> Result<usize, Error> {
+    let mut size = 0;
+    let mut decoded = 0;
+    while decoded < line_width {
+        let &header = data.get(size).ok_or(Error::InvalidImage)?;
+        let (length, repeated) = packet_length(header);
+        size += 1 + if repeated { 1 } else { length };
+        decoded += length;
+    }
+
+    if decoded == line_width && size <= data.len() {
+        Ok(size)
+    } else {
+        Err(Error::InvalidImage)
+    }
+}
+
+/// Get the number of decoded bytes of a packet, and whether they are repeated
+fn packet_length(header: u8) -> (usize, bool) {
+    match header {
+        0..=127 => (usize::from(header) + 1, false),
+        128 => (0, false),
+        129..=255 => (257 - usize::from(header), true),
+    }
+}
+
+#[cfg(all(feature = "std", feature = "draw-target"))]
+/// Compress a buffer
+///
+/// The result can be stored in a file and included in a firmware as a
+/// [`CompressedImage`].
+#[must_use]
+pub fn compress_buffer<const WIDTH: usize, const HEIGHT: usize, const BYTE_SIZE: usize>(
+    buffer: &Buffer<WIDTH, HEIGHT, BYTE_SIZE>,
+) -> Vec<u8> {
+    let width = u16::try_from(WIDTH).unwrap_or(u16::MAX);
+    let height = u16::try_from(HEIGHT).unwrap_or(u16::MAX);
+    let line_width = WIDTH.div_ceil(8).max(1);
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&width.to_le_bytes());
+    data.extend_from_slice(&height.to_le_bytes());
+    for plane in [buffer.black_buffer(), buffer.chromatic_buffer()] {
+        for line in plane.chunks(line_width) {
+            compress_line(line, &mut data);
+        }
+    }
+
+    data
+}
+
+#[cfg(all(feature = "std", feature = "draw-target"))]
+/// Compress a line and append it to data
+///
+/// Runs of two or more bytes are repeated, and all other bytes are copied
+/// literally.
+#[allow(clippy::cast_possible_truncation)]
+fn compress_line(mut line: &[u8], data: &mut Vec<u8>) {
+    while let Some(&first) = line.first() {
+        let run = line
+            .iter()
+            .take(MAX_PACKET_LENGTH)
+            .take_while(|&&byte| byte == first)
+            .count();
+
+        let length = if run > 1 {
+            data.push((257 - run) as u8);
+            data.push(first);
+            run
+        } else {
+            let length = line
+                .windows(2)
+                .take(MAX_PACKET_LENGTH)
+                .position(|pair| matches!(*pair, [previous, next] if previous == next))
+                .unwrap_or(line.len())
+                .min(MAX_PACKET_LENGTH);
+            data.push((length - 1) as u8);
+            data.extend_from_slice(&line[..length]);
+            length
+        };
+
+        line = &line[length..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "std", feature = "draw-target"))]
+    use embedded_graphics_core::draw_target::DrawTarget;
+    #[cfg(all(feature = "std", feature = "draw-target"))]
+    use embedded_graphics_core::geometry::Point;
+    #[cfg(all(feature = "std", feature = "draw-target"))]
+    use embedded_graphics_core::Pixel;
+
+    use crate::Epd1in54bV2;
+    use crate::Epd2in9bV4;
+
+    #[cfg(all(feature = "std", feature = "draw-target"))]
+    use crate::Color;
+    #[cfg(all(feature = "std", feature = "draw-target"))]
+    use crate::Epd1in54Buffer;
+
+    #[test]
+    fn decoder_packets() {
+        let data = [0x02, 0x01, 0x02, 0x03, 0xfd, 0xaa, 0x80, 0x00, 0x04];
+        let mut decoded = [0x00; 16];
+        let length = Decoder::new(&data).read(&mut decoded);
+        assert_eq!(
+            &decoded[..length],
+            [0x01, 0x02, 0x03, 0xaa, 0xaa, 0xaa, 0xaa, 0x04]
+        );
+    }
+
+    #[test]
+    fn decoder_stops_at_truncated_data() {
+        let mut decoder = Decoder::new(&[0x03, 0x01, 0x02]);
+        assert_eq!(decoder.next(), Some(0x01));
+        assert_eq!(decoder.next(), Some(0x02));
+        assert_eq!(decoder.next(), None);
+    }
+
+    #[test]
+    fn lines_are_split_at_packets() {
+        let data = [0xfe, 0xff, 0x00, 0x00, 0xfd, 0x11];
+        let lines: [&[u8]; 2] = [&[0xfe, 0xff, 0x00, 0x00], &[0xfd, 0x11]];
+        assert!(compressed_lines(&data, 4).eq(lines));
+        assert_eq!(
+            compressed_line_size(&[0xfb, 0x00], 4),
+            Err(Error::InvalidImage)
+        );
+        assert_eq!(
+            compressed_line_size(&[0x03, 0x00], 4),
+            Err(Error::InvalidImage)
+        );
+    }
+
+    #[test]
+    fn image_must_match_panel() {
+        let blank_line = [0xe8, 0xff];
+        let mut data = [0x00; HEADER_SIZE + 2 * 200 * 2];
+        data[..HEADER_SIZE].copy_from_slice(&[200, 0, 200, 0]);
+        for line in data[HEADER_SIZE..].chunks_mut(2) {
+            line.copy_from_slice(&blank_line);
+        }
+
+        let image = CompressedImage::new(&data);
+        assert!(image.planes::<Epd1in54bV2>().is_ok());
+        assert_eq!(
+            image.planes::<Epd2in9bV4>().map(|_| ()),
+            Err(Error::InvalidImage)
+        );
+
+        let truncated = CompressedImage::new(&data[..data.len() - 1]);
+        assert_eq!(
+            truncated.planes::<Epd1in54bV2>().map(|_| ()),
+            Err(Error::InvalidImage)
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "draw-target"))]
+    fn compressed_buffer_is_decoded() {
+        let mut buffer = Epd1in54Buffer::new();
+        let diagonal =
+            (0_i32..200_i32).map(|position| Pixel(Point::new(position, position), Color::Black));
+        let Ok(()) = buffer.draw_iter(diagonal);
+        let Ok(()) = buffer.draw_iter([Pixel(Point::new(199, 0), Color::Chromatic)]);
+
+        let data = compress_buffer(&buffer);
+        assert!(data.len() < 2 * 1000);
+
+        let planes = CompressedImage::new(&data).planes::<Epd1in54bV2>();
+        assert!(planes.is_ok_and(|(black, chromatic)| {
+            Decoder::new(black).eq(buffer.black_buffer().iter().copied())
+                && Decoder::new(chromatic).eq(buffer.chromatic_buffer().iter().copied())
+        }));
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "draw-target"))]
+    fn long_lines_are_split_in_packets() {
+        let line = (0..=255).chain([0x00; 200]).collect::<Vec<u8>>();
+        let mut data = Vec::new();
+        compress_line(&line, &mut data);
+
+        assert_eq!(data.len(), 2 * (1 + 128) + 2 * 2);
+        assert!(Decoder::new(&data).eq(line));
+    }
+}
@@ -0,0 +1,332 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Graphical buffer for a horizontal strip of the screen
+
+use core::convert::Infallible;
+
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::Dimensions;
+use embedded_graphics_core::geometry::OriginDimensions;
+use embedded_graphics_core::geometry::Point;
+use embedded_graphics_core::geometry::Size;
+use embedded_graphics_core::primitives::Rectangle;
+use embedded_graphics_core::Pixel;
+
+use crate::buffer::fill_plane;
+use crate::buffer::get_index_and_offset;
+use crate::buffer::physical_point;
+use crate::buffer::plane_bits;
+use crate::buffer::rotated_size;
+use crate::buffer::set_bits;
+use crate::Color;
+use crate::Rotation;
+
+/// A buffer to draw tri-colors graphics on a horizontal strip of the screen
+///
+/// `WIDTH` and `HEIGHT` are the screen width and height in pixels, `ROWS` is
+/// the number of physical rows in the strip, and `BYTE_SIZE` is the strip
+/// size in bytes (⌈width ÷ 8⌉ × rows).
+///
+/// Graphics are drawn in screen coordinates, and only the pixels falling in
+/// the current strip are stored.
+/// The strip can therefore be moved along the screen while drawing the same
+/// graphics, so that the whole screen is rendered with a fraction of the
+/// memory of a [`Buffer`](crate::Buffer).
+#[derive(Debug)]
+pub struct Strip<const WIDTH: usize, const HEIGHT: usize, const ROWS: usize, const BYTE_SIZE: usize>
+{
+    /// Screen rotation
+    rotation: Rotation,
+
+    /// First physical row of the strip
+    first_row: usize,
+
+    /// Black part of the strip
+    black: [u8; BYTE_SIZE],
+
+    /// Chromatic part of the strip
+    chromatic: [u8; BYTE_SIZE],
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize, const ROWS: usize, const BYTE_SIZE: usize>
+    Strip<WIDTH, HEIGHT, ROWS, BYTE_SIZE>
+{
+    /// Size of a row in bytes
+    const LINE_WIDTH: usize = WIDTH.div_ceil(8);
+
+    /// Create a new strip at the top of the screen
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            rotation: Rotation::Rotate0,
+            first_row: 0,
+            black: [255; BYTE_SIZE],
+            chromatic: [255; BYTE_SIZE],
+        }
+    }
+
+    /// Get the black part of the strip
+    #[must_use]
+    pub fn black_buffer(&self) -> &[u8] {
+        &self.black
+    }
+
+    /// Get the chromatic part of the strip
+    #[must_use]
+    pub fn chromatic_buffer(&self) -> &[u8] {
+        &self.chromatic
+    }
+
+    /// Set screen rotation
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.rotation = rotation;
+    }
+
+    /// Get screen rotation
+    #[must_use]
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// Get the first physical row of the strip
+    #[must_use]
+    pub fn first_row(&self) -> usize {
+        self.first_row
+    }
+
+    /// Get the number of physical rows of the strip
+    ///
+    /// The last strip of the screen can be shorter than `ROWS`.
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        ROWS.min(HEIGHT.saturating_sub(self.first_row))
+    }
+
+    /// Move the strip to a physical row and clear it to white
+    pub fn move_to(&mut self, first_row: usize) {
+        self.first_row = first_row;
+        self.black = [255; BYTE_SIZE];
+        self.chromatic = [255; BYTE_SIZE];
+    }
+
+    /// Convert a physical row to a strip row
+    ///
+    /// Return `None` if the row lies outside of the strip.
+    #[allow(clippy::cast_sign_loss)]
+    fn strip_row(&self, row: i32) -> Option<usize> {
+        (row as usize)
+            .checked_sub(self.first_row)
+            .filter(|row| *row < self.rows())
+    }
+
+    /// Fill an area in physical coordinates with a color
+    ///
+    /// The area must lie within the screen, and it is clipped to the strip.
+    #[allow(clippy::cast_sign_loss)]
+    fn fill_physical(&mut self, area: &Rectangle, color: Color) {
+        let Some(bottom_right) = area.bottom_right() else {
+            return;
+        };
+        let Some(last_strip_row) = (self.first_row + self.rows()).checked_sub(1) else {
+            return;
+        };
+        let first_row = (area.top_left.y as usize).max(self.first_row);
+        let last_row = (bottom_right.y as usize).min(last_strip_row);
+        if first_row > last_row {
+            return;
+        }
+
+        let columns = area.top_left.x as usize..=bottom_right.x as usize;
+        let rows = first_row - self.first_row..=last_row - self.first_row;
+
+        let (black, chromatic) = plane_bits(color);
+        if let Some(value) = black {
+            fill_plane(
+                &mut self.black,
+                Self::LINE_WIDTH,
+                columns.clone(),
+                rows.clone(),
+                value,
+            );
+        }
+        if let Some(value) = chromatic {
+            fill_plane(&mut self.chromatic, Self::LINE_WIDTH, columns, rows, value);
+        }
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize, const ROWS: usize, const BYTE_SIZE: usize> Default
+    for Strip<WIDTH, HEIGHT, ROWS, BYTE_SIZE>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize, const ROWS: usize, const BYTE_SIZE: usize> DrawTarget
+    for Strip<WIDTH, HEIGHT, ROWS, BYTE_SIZE>
+{
+    type Error = Infallible;
+
+    type Color = Color;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounding_box = self.bounding_box();
+
+        for Pixel(point, color) in pixels {
+            if !bounding_box.contains(point) {
+                continue;
+            }
+
+            let Point { x, y } = physical_point::<WIDTH, HEIGHT>(self.rotation, point);
+            let Some(row) = self.strip_row(y) else {
+                continue;
+            };
+            let (index, offset) = get_index_and_offset(Self::LINE_WIDTH, x, row as i32);
+            if index >= BYTE_SIZE {
+                continue;
+            }
+
+            let mask: u8 = 0b1000_0000 >> offset;
+            let (black, chromatic) = plane_bits(color);
+            if let Some(value) = black {
+                set_bits(&mut self.black[index], mask, value);
+            }
+            if let Some(value) = chromatic {
+                set_bits(&mut self.chromatic[index], mask, value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fill an area with a single color
+    ///
+    /// Only the part of the area falling in the strip is filled.
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+        if let Some(bottom_right) = area.bottom_right() {
+            let area = Rectangle::with_corners(
+                physical_point::<WIDTH, HEIGHT>(self.rotation, area.top_left),
+                physical_point::<WIDTH, HEIGHT>(self.rotation, bottom_right),
+            );
+            self.fill_physical(&area, color);
+        }
+
+        Ok(())
+    }
+
+    /// Fill the whole strip with a single color
+    #[allow(clippy::cast_possible_truncation)]
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let area = Rectangle::new(Point::zero(), Size::new(WIDTH as u32, HEIGHT as u32));
+        self.fill_physical(&area, color);
+
+        Ok(())
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize, const ROWS: usize, const BYTE_SIZE: usize>
+    OriginDimensions for Strip<WIDTH, HEIGHT, ROWS, BYTE_SIZE>
+{
+    fn size(&self) -> Size {
+        rotated_size::<WIDTH, HEIGHT>(self.rotation)
+    }
+}
+
+/// A strip of 20 rows for 1.54 in displays
+#[allow(clippy::module_name_repetitions)]
+pub type Epd1in54Strip = Strip<200, 200, 20, 500>;
+
+/// A strip of 25 rows for 2.13 in displays
+#[allow(clippy::module_name_repetitions)]
+pub type Epd2in13Strip = Strip<122, 250, 25, 400>;
+
+/// A strip of 37 rows for 2.9 in displays
+#[allow(clippy::module_name_repetitions)]
+pub type Epd2in9Strip = Strip<128, 296, 37, 592>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Buffer;
+
+    /// A small non-square buffer
+    type TestBuffer = Buffer<12, 10, 20>;
+
+    /// A strip for the test buffer, not dividing its height
+    type TestStrip = Strip<12, 10, 4, 8>;
+
+    /// Draw the same graphics on a buffer or a strip
+    fn draw<D: DrawTarget<Color = Color, Error = Infallible>>(target: &mut D) {
+        let Ok(()) = target.fill_solid(
+            &Rectangle::new(Point::new(1, 1), Size::new(7, 6)),
+            Color::Chromatic,
+        );
+        let Ok(()) = target.fill_solid(
+            &Rectangle::new(Point::new(3, 2), Size::new(9, 1)),
+            Color::Black,
+        );
+        let pixels = [
+            Pixel(Point::new(0, 0), Color::Black),
+            Pixel(Point::new(9, 5), Color::Chromatic),
+            Pixel(Point::new(2, 9), Color::Black),
+            Pixel(Point::new(11, 7), Color::Black),
+        ];
+        let Ok(()) = target.draw_iter(pixels);
+    }
+
+    #[test]
+    fn strips_match_buffer() {
+        for rotation in [
+            Rotation::Rotate0,
+            Rotation::Rotate90,
+            Rotation::Rotate180,
+            Rotation::Rotate270,
+        ] {
+            let mut buffer = TestBuffer::new();
+            buffer.set_rotation(rotation);
+            draw(&mut buffer);
+
+            let mut strip = TestStrip::new();
+            strip.set_rotation(rotation);
+            for first_row in (0..10).step_by(4) {
+                strip.move_to(first_row);
+                draw(&mut strip);
+
+                let range = first_row * 2..(first_row + strip.rows()) * 2;
+                let length = range.len();
+                assert_eq!(
+                    &strip.black_buffer()[..length],
+                    &buffer.black_buffer()[range.clone()],
+                    "{rotation:?}, row {first_row}"
+                );
+                assert_eq!(
+                    &strip.chromatic_buffer()[..length],
+                    &buffer.chromatic_buffer()[range],
+                    "{rotation:?}, row {first_row}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn last_strip_is_shorter() {
+        let mut strip = TestStrip::new();
+        assert_eq!(strip.rows(), 4);
+
+        strip.move_to(8);
+        assert_eq!(strip.rows(), 2);
+    }
+}
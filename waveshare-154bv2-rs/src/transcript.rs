@@ -0,0 +1,393 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Recording transport for testing displays on host
+//!
+//! SPI bus, pins and delay record everything the display does in a shared
+//! transcript, so that tests can compare it byte by byte.
+
+extern crate alloc;
+
+use alloc::rc::Rc;
+#[cfg(feature = "async")]
+use alloc::sync::Arc;
+#[cfg(feature = "async")]
+use alloc::task::Wake;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use core::cell::RefCell;
+use core::convert::Infallible;
+#[cfg(feature = "async")]
+use core::future::ready;
+#[cfg(feature = "async")]
+use core::future::Future;
+#[cfg(feature = "async")]
+use core::pin::pin;
+#[cfg(feature = "async")]
+use core::task::Context;
+#[cfg(feature = "async")]
+use core::task::Poll;
+#[cfg(feature = "async")]
+use core::task::Waker;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::ErrorType as DigitalErrorType;
+use embedded_hal::digital::InputPin;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::ErrorType as SpiErrorType;
+use embedded_hal::spi::Operation;
+use embedded_hal::spi::SpiDevice;
+
+/// An event on the display hardware
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Event {
+    /// The reset pin was set high or low
+    Reset(bool),
+
+    /// The display waited for a number of milliseconds
+    Delay(u32),
+
+    /// The display waited until it was no longer busy
+    WaitUntilIdle,
+
+    /// A command was sent
+    Command(u8),
+
+    /// Data was sent, merged over consecutive writes
+    Data(Vec<u8>),
+}
+
+/// State shared by all recording devices
+#[derive(Default)]
+struct Log {
+    /// Level of the DC pin
+    data_mode: bool,
+
+    /// Recorded events
+    events: Vec<Event>,
+
+    /// Number of SPI writes
+    writes: usize,
+}
+
+/// A transcript of events on the display hardware
+#[derive(Default)]
+pub(crate) struct Transcript {
+    /// Shared state
+    log: Rc<RefCell<Log>>,
+}
+
+impl Transcript {
+    /// Create an empty transcript
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create recording devices for a display
+    pub(crate) fn hardware(&self) -> (RecordingSpi, BusyPin, ResetPin, DcPin, RecordingDelay) {
+        (
+            RecordingSpi(self.handle()),
+            BusyPin(self.handle()),
+            ResetPin(self.handle()),
+            DcPin(self.handle()),
+            RecordingDelay(self.handle()),
+        )
+    }
+
+    /// Take all events recorded so far
+    pub(crate) fn take(&self) -> Vec<Event> {
+        core::mem::take(&mut self.log.borrow_mut().events)
+    }
+
+    /// Take the number of SPI writes so far
+    ///
+    /// Unlike events, writes are not merged.
+    pub(crate) fn take_writes(&self) -> usize {
+        core::mem::take(&mut self.log.borrow_mut().writes)
+    }
+
+    /// Create another handle to the same transcript
+    fn handle(&self) -> Self {
+        Self {
+            log: Rc::clone(&self.log),
+        }
+    }
+
+    /// Record an event
+    fn record(&self, event: Event) {
+        self.log.borrow_mut().events.push(event);
+    }
+
+    /// Record bytes written to SPI bus as command or data
+    fn write(&self, bytes: &[u8]) {
+        let mut log = self.log.borrow_mut();
+        log.writes += 1;
+        if log.data_mode {
+            if let Some(&mut Event::Data(ref mut data)) = log.events.last_mut() {
+                data.extend_from_slice(bytes);
+            } else {
+                log.events.push(Event::Data(bytes.to_vec()));
+            }
+        } else {
+            log.events
+                .extend(bytes.iter().map(|&command| Event::Command(command)));
+        }
+    }
+}
+
+/// A SPI device recording written bytes
+pub(crate) struct RecordingSpi(Transcript);
+
+impl SpiErrorType for RecordingSpi {
+    type Error = Infallible;
+}
+
+impl SpiDevice for RecordingSpi {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        for operation in operations {
+            if let Operation::Write(bytes) = *operation {
+                self.0.write(bytes);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::spi::SpiDevice for RecordingSpi {
+    fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> impl Future<Output = Result<(), Self::Error>> {
+        ready(SpiDevice::transaction(self, operations))
+    }
+}
+
+/// A busy pin recording waits, and never busy
+pub(crate) struct BusyPin(Transcript);
+
+impl DigitalErrorType for BusyPin {
+    type Error = Infallible;
+}
+
+impl InputPin for BusyPin {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.0.record(Event::WaitUntilIdle);
+        Ok(false)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.0.record(Event::WaitUntilIdle);
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::digital::Wait for BusyPin {
+    fn wait_for_high(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+        self.0.record(Event::WaitUntilIdle);
+        ready(Ok(()))
+    }
+
+    fn wait_for_low(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+        self.0.record(Event::WaitUntilIdle);
+        ready(Ok(()))
+    }
+
+    fn wait_for_rising_edge(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+        ready(Ok(()))
+    }
+
+    fn wait_for_falling_edge(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+        ready(Ok(()))
+    }
+
+    fn wait_for_any_edge(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+        ready(Ok(()))
+    }
+}
+
+/// A reset pin recording its level
+pub(crate) struct ResetPin(Transcript);
+
+impl DigitalErrorType for ResetPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for ResetPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.record(Event::Reset(false));
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.record(Event::Reset(true));
+        Ok(())
+    }
+}
+
+/// A DC pin selecting whether SPI writes are recorded as commands or data
+pub(crate) struct DcPin(Transcript);
+
+impl DigitalErrorType for DcPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for DcPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.log.borrow_mut().data_mode = false;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.log.borrow_mut().data_mode = true;
+        Ok(())
+    }
+}
+
+/// A delay recording waits in milliseconds, and returning immediately
+pub(crate) struct RecordingDelay(Transcript);
+
+impl DelayNs for RecordingDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.record(Event::Delay(ns / 1_000_000));
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.0.record(Event::Delay(ms));
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for RecordingDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        DelayNs::delay_ns(self, ns);
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        DelayNs::delay_ms(self, ms);
+    }
+}
+
+#[cfg(feature = "async")]
+/// A waker doing nothing
+struct NoopWaker;
+
+#[cfg(feature = "async")]
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+#[cfg(feature = "async")]
+/// Run a future to completion by polling it repeatedly
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// Events for a command with data
+fn command(command: u8, data: &[u8]) -> [Event; 2] {
+    [Event::Command(command), Event::Data(data.to_vec())]
+}
+
+/// Expected transcript of initializing a 1.54 in B v2 panel
+pub(crate) fn initialization() -> Vec<Event> {
+    [
+        vec![
+            Event::Reset(true),
+            Event::Delay(10),
+            Event::Reset(false),
+            Event::Delay(10),
+            Event::Reset(true),
+            Event::Delay(200),
+            Event::WaitUntilIdle,
+            Event::Command(0x12),
+            Event::WaitUntilIdle,
+        ],
+        command(0x01, &[0xc7, 0x00, 0x01]).to_vec(),
+        command(0x11, &[0x01]).to_vec(),
+        command(0x44, &[0x00, 0x18]).to_vec(),
+        command(0x45, &[0xc7, 0x00, 0x00, 0x00]).to_vec(),
+        command(0x3c, &[0x05]).to_vec(),
+        command(0x21, &[0x00, 0x00]).to_vec(),
+        command(0x4e, &[0x00]).to_vec(),
+        command(0x4f, &[0xc7, 0x00]).to_vec(),
+        vec![Event::WaitUntilIdle],
+    ]
+    .concat()
+}
+
+/// Expected transcript of a full refresh of a 1.54 in B v2 panel
+pub(crate) fn full_refresh() -> Vec<Event> {
+    [
+        command(0x22, &[0xf7]).to_vec(),
+        vec![Event::Command(0x20), Event::WaitUntilIdle],
+    ]
+    .concat()
+}
+
+/// Expected transcript of clearing a 1.54 in B v2 panel
+pub(crate) fn clearing() -> Vec<Event> {
+    [
+        command(0x24, &[0xff; 5000]).to_vec(),
+        command(0x26, &[0x00; 5000]).to_vec(),
+        full_refresh(),
+    ]
+    .concat()
+}
+
+/// Expected transcript of releasing a 1.54 in B v2 panel
+pub(crate) fn releasing() -> Vec<Event> {
+    [command(0x10, &[0x01]).to_vec(), vec![Event::Delay(200)]].concat()
+}
+
+#[cfg(feature = "draw-target")]
+/// Expected transcript of drawing a buffer for the first time
+///
+/// The buffer has a black pixel at the top-left corner, and a chromatic
+/// pixel at the bottom-right corner.
+pub(crate) fn first_drawing() -> Vec<Event> {
+    let mut black = vec![0xff; 5000];
+    black[0] = 0x7f;
+    let mut chromatic = vec![0x00; 5000];
+    chromatic[4999] = 0x01;
+
+    [
+        command(0x24, &black).to_vec(),
+        command(0x26, &chromatic).to_vec(),
+        full_refresh(),
+    ]
+    .concat()
+}
+
+#[cfg(feature = "draw-target")]
+/// Expected transcript of drawing a buffer with an additional black pixel
+///
+/// The pixel is in the second byte of the second row.
+pub(crate) fn second_drawing() -> Vec<Event> {
+    [
+        command(0x44, &[0x01, 0x01]).to_vec(),
+        command(0x45, &[0xc6, 0x00, 0xc6, 0x00]).to_vec(),
+        command(0x4e, &[0x01]).to_vec(),
+        command(0x4f, &[0xc6, 0x00]).to_vec(),
+        command(0x24, &[0x7f]).to_vec(),
+        command(0x44, &[0x00, 0x18]).to_vec(),
+        command(0x45, &[0xc7, 0x00, 0x00, 0x00]).to_vec(),
+        command(0x4e, &[0x00]).to_vec(),
+        command(0x4f, &[0xc7, 0x00]).to_vec(),
+        full_refresh(),
+    ]
+    .concat()
+}
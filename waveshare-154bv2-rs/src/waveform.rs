@@ -0,0 +1,55 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Selection of waveforms used for refreshing the display
+
+/// Display update sequence flag for loading temperature from the sensor
+const LOAD_TEMPERATURE: u8 = 0b0010_0000;
+
+/// Display update sequence flag for loading the LUT from OTP
+const LOAD_LUT: u8 = 0b0001_0000;
+
+/// Source of the waveform used for refreshing the display
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum WaveformSource {
+    /// Temperature is measured by the sensor, and the LUT is loaded from OTP
+    Builtin,
+
+    /// Temperature is written externally, and the LUT is loaded from OTP
+    ///
+    /// The content of the temperature register is stored, so that it can be
+    /// written again after being overwritten.
+    ExternalTemperature([u8; 2]),
+
+    /// A custom LUT is written externally
+    CustomLut,
+}
+
+impl WaveformSource {
+    /// Adapt a display update sequence to the waveform source
+    ///
+    /// Steps loading temperature or LUT are removed from the sequence when
+    /// they would overwrite the externally written values.
+    pub(crate) fn update_sequence(self, sequence: u8) -> u8 {
+        match self {
+            Self::Builtin => sequence,
+            Self::ExternalTemperature(_) => sequence & !LOAD_TEMPERATURE,
+            Self::CustomLut => sequence & !(LOAD_TEMPERATURE | LOAD_LUT),
+        }
+    }
+}
+
+/// Convert a temperature to the content of the temperature register
+///
+/// The register holds a 12 bits two's complement value in units of 1/16 °C,
+/// left-aligned in two bytes.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub(crate) fn temperature_register(celsius: f32) -> [u8; 2] {
+    let value = (celsius * 16.0).clamp(-2048.0, 2047.0) as i16;
+    ((value as u16) << 4).to_be_bytes()
+}
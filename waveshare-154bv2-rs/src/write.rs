@@ -0,0 +1,56 @@
+// Copyright Claudio Mattera 2024-2025.
+//
+// Distributed under the MIT License or the Apache 2.0 License at your option.
+// See the accompanying files LICENSE-MIT.txt and LICENSE-APACHE-2.0.txt, or
+// online at
+// https://opensource.org/licenses/MIT
+// https://opensource.org/licenses/Apache-2.0
+
+//! Strategies for writing to SPI bus
+
+/// Default limit of bytes per SPI transfer on Linux
+///
+/// See <https://raspberrypi.stackexchange.com/questions/65595/spi-transfer-fails-with-buffer-size-greater-than-4096>
+const LINUX_TRANSFER_LIMIT: usize = 4096;
+
+/// A strategy for writing data to SPI bus
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum WriteStrategy {
+    /// Write all data in a single transaction
+    Bulk,
+
+    /// Write data in transactions of at most the given number of bytes
+    ///
+    /// This is needed when the SPI bus limits the size of transactions, e.g.
+    /// to the size of its DMA buffers.
+    Chunked(usize),
+
+    /// Write each byte in a separate transaction
+    ///
+    /// This is the slowest strategy, only needed for SPI buses that cannot
+    /// handle longer transactions.
+    PerByte,
+}
+
+impl WriteStrategy {
+    /// Get the size of transactions for writing some data
+    pub(crate) fn chunk_size(self, length: usize) -> usize {
+        match self {
+            Self::Bulk => length.max(1),
+            Self::Chunked(size) => size.max(1),
+            Self::PerByte => 1,
+        }
+    }
+}
+
+impl Default for WriteStrategy {
+    /// Write in bulk, except on Linux where transactions are limited to 4096
+    /// bytes by default
+    fn default() -> Self {
+        if cfg!(target_os = "linux") {
+            Self::Chunked(LINUX_TRANSFER_LIMIT)
+        } else {
+            Self::Bulk
+        }
+    }
+}